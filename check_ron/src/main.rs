@@ -0,0 +1,152 @@
+use clap::Parser;
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use game_ron::*;
+
+/// Checks `*.game.ron`, `*.puzzle.ron` and `*.list.ron` files.
+/// With no files, checks the default list.rons in the assets directory and all files listed in them.
+#[derive(Parser)]
+#[command(version, about, long_about = None)]
+struct Cmd {
+    /// `*.game.ron`, `*.puzzle.ron` or `*.list.ron`
+    files: Vec<PathBuf>,
+
+    /// Root of asset paths written in the files.
+    #[arg(long, default_value = "assets")]
+    assets: PathBuf,
+}
+
+const DEFAULT_LIST_RONS: [&str; 2] = ["ron/index.list.ron", "ron/puzzles.list.ron"];
+
+/// A problem in a file.
+struct Diagnostic {
+    file: PathBuf,
+    message: String,
+}
+
+struct Checker {
+    assets: PathBuf,
+    diagnostics: Vec<Diagnostic>,
+    /// Canonicalized list.rons being checked, to detect cycles of lists.
+    checking_lists: HashSet<PathBuf>,
+}
+
+impl Checker {
+    fn report(&mut self, file: &Path, message: impl Into<String>) {
+        self.diagnostics.push(Diagnostic {
+            file: file.to_path_buf(),
+            message: message.into(),
+        });
+    }
+
+    fn read<T: serde::de::DeserializeOwned>(&mut self, file: &Path) -> Option<T> {
+        let buf = match std::fs::read_to_string(file) {
+            Ok(buf) => buf,
+            Err(e) => {
+                self.report(file, format!("Failed to read: {}", e));
+                return None;
+            }
+        };
+        match ron::from_str(&buf) {
+            Ok(r) => Some(r),
+            Err(e) => {
+                self.report(file, format!("Failed to parse: {}", e));
+                None
+            }
+        }
+    }
+
+    /// Paths of other schemes (e.g. `embedded://`) are not checked.
+    fn asset_exists(&self, path: &str) -> bool {
+        path.contains("://") || self.assets.join(path).is_file()
+    }
+
+    fn check_game_ron(&mut self, file: &Path) {
+        let Some(game_ron) = self.read::<GameRon>(file) else { return; };
+
+        let mut errors = game_ron.validate();
+        errors.extend(game_ron.validate_asset_paths(|p| self.asset_exists(p)));
+        for e in errors {
+            self.report(file, e.to_string());
+        }
+    }
+
+    /// Only values independent from game.ron are checked.
+    fn check_puzzle_ron(&mut self, file: &Path) {
+        let Some(puzzle_ron) = self.read::<PuzzleRon>(file) else { return; };
+
+        for e in puzzle_ron.validate() {
+            self.report(file, e.to_string());
+        }
+    }
+
+    fn check_list_ron(&mut self, file: &Path) {
+        let key = file.canonicalize().unwrap_or_else(|_| file.to_path_buf());
+        if self.checking_lists.contains(&key) {
+            self.report(file, "Included again through a cycle of lists.");
+            return;
+        }
+        let Some(list_ron) = self.read::<ListRon>(file) else { return; };
+        self.checking_lists.insert(key.clone());
+
+        for (i, item) in list_ron.list.iter().enumerate() {
+            if list_ron.list[..i].iter().any(|x| x.name == item.name) {
+                self.report(file, format!("list[{}].name: `{}` is duplicated.", i, item.name));
+            }
+            if self.asset_exists(&item.path) {
+                let item_file = self.assets.join(&item.path);
+                self.check(&item_file);
+            } else {
+                self.report(file, format!("list[{}].path: `{}` does not exist.", i, item.path));
+            }
+        }
+        self.checking_lists.remove(&key);
+    }
+
+    fn check(&mut self, file: &Path) {
+        let name = file.to_string_lossy();
+        if name.ends_with(".list.ron") {
+            self.check_list_ron(file);
+        } else if name.ends_with(".game.ron") {
+            self.check_game_ron(file);
+        } else if name.ends_with(".puzzle.ron") {
+            self.check_puzzle_ron(file);
+        } else {
+            self.report(file, "Unknown file type. Expected `*.game.ron`, `*.puzzle.ron` or `*.list.ron`.");
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let args = Cmd::parse();
+
+    let files = if args.files.is_empty() {
+        DEFAULT_LIST_RONS.map(|f| args.assets.join(f)).to_vec()
+    } else {
+        args.files
+    };
+
+    let mut checker = Checker {
+        assets: args.assets,
+        diagnostics: vec![],
+        checking_lists: HashSet::new(),
+    };
+    for file in files.iter() {
+        checker.check(file);
+    }
+
+    for d in checker.diagnostics.iter() {
+        eprintln!("{}: {}", d.file.display(), d.message);
+    }
+
+    if checker.diagnostics.is_empty() {
+        println!("{} file(s) checked. No problems found.", files.len());
+        ExitCode::SUCCESS
+    } else {
+        eprintln!("{} problem(s) found.", checker.diagnostics.len());
+        ExitCode::FAILURE
+    }
+}
@@ -73,6 +73,126 @@ pub struct BallLevelSettingRon {
     /// Index of `effects`.
     #[serde(default)]
     pub effect_index: Option<usize>,
+
+    #[serde(default)]
+    pub shape: BallShapeRon,
+    /// Offset of the image from the center of the shape,
+    /// relative to `physics_radius`.
+    #[serde(default)]
+    pub view_offset: Vec2,
+
+    #[serde(default)]
+    pub physics: BallPhysicsRon,
+}
+
+/// Physical properties of a ball level.
+/// `friction` and `restitution` are of `ball_physics` if `None`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[derive(Reflect)]
+pub struct BallPhysicsRon {
+    #[serde(default)]
+    pub friction: Option<FrictionRon>,
+    #[serde(default)]
+    pub restitution: Option<RestitutionRon>,
+    /// Mass per area. 1.0 if neither `density` nor `mass` is set.
+    #[serde(default)]
+    pub density: Option<f32>,
+    /// Mass of the ball grown up. Set either `density` or this.
+    #[serde(default)]
+    pub mass: Option<f32>,
+    #[serde(default)]
+    pub linear_damping: Option<f32>,
+    #[serde(default)]
+    pub angular_damping: Option<f32>,
+}
+
+/// Collider shape of a ball.
+/// Sizes are relative to `physics_radius` and the shape must be within it.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[derive(Reflect)]
+pub enum BallShapeRon {
+    #[default]
+    Circle,
+    /// An ellipse approximated by a convex polygon of `vertices`.
+    Ellipse {
+        width: f32,
+        height: f32,
+        vertices: usize,
+    },
+    /// A convex hull of `vertices` with rounded corners of `radius`.
+    RoundedPolygon {
+        vertices: Vec<Vec2>,
+        radius: f32,
+    },
+    /// A vertical capsule. `length` is of the straight part.
+    Capsule {
+        length: f32,
+        radius: f32,
+    },
+    /// Circles of (center, radius).
+    Circles(Vec<(Vec2, f32)>),
+}
+impl BallShapeRon {
+    /// Radius of the largest circle in the shape (approximately).
+    pub fn inner_radius(&self) -> f32 {
+        match self {
+            Self::Circle => 1.,
+            Self::Ellipse { width, height, .. } => width.min(*height) / 2.,
+            Self::RoundedPolygon { vertices, radius } => {
+                // Distance from the center to the nearest edge.
+                let inset = vertices.iter().zip(vertices.iter().cycle().skip(1))
+                    .map(|(a, b)| {
+                        let ab = *b - *a;
+                        let t = (-a.dot(ab) / ab.length_squared().max(f32::EPSILON)).clamp(0., 1.);
+                        (*a + ab * t).length()
+                    })
+                    .fold(f32::INFINITY, f32::min);
+                if inset.is_finite() { inset + radius } else { *radius }
+            },
+            Self::Capsule { radius, .. } => *radius,
+            Self::Circles(circles) => circles.iter()
+                .map(|(_, r)| *r)
+                .fold(0., f32::max),
+        }
+    }
+
+    /// Area of the shape. Overlaps of `Circles` are counted for each circle.
+    pub fn area(&self) -> f32 {
+        use std::f32::consts::PI;
+        match self {
+            Self::Circle => PI,
+            Self::Ellipse { width, height, vertices } => {
+                let n = *vertices as f32;
+                n / 2. * (2. * PI / n).sin() * (width / 2.) * (height / 2.)
+            },
+            Self::RoundedPolygon { vertices, radius } => {
+                // Vertices are in order around the polygon.
+                let (area2, perimeter) = vertices.iter()
+                    .zip(vertices.iter().cycle().skip(1))
+                    .fold((0., 0.), |(a, p), (v0, v1)| (a + v0.perp_dot(*v1), p + v0.distance(*v1)));
+                area2.abs() / 2. + perimeter * radius + PI * radius * radius
+            },
+            Self::Capsule { length, radius } => length * radius * 2. + PI * radius * radius,
+            Self::Circles(circles) => circles.iter()
+                .map(|(_, r)| PI * r * r)
+                .sum(),
+        }
+    }
+
+    /// Distance from the center to the farthest point of the shape.
+    pub fn outer_radius(&self) -> f32 {
+        match self {
+            Self::Circle => 1.,
+            Self::Ellipse { width, height, .. } => width.max(*height) / 2.,
+            Self::RoundedPolygon { vertices, radius } => vertices.iter()
+                .map(|v| v.length() + radius)
+                .fold(0., f32::max),
+            Self::Capsule { length, radius } => length / 2. + radius,
+            Self::Circles(circles) => circles.iter()
+                .map(|(c, r)| c.length() + r)
+                .fold(0., f32::max),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -99,6 +219,69 @@ pub struct BottleRon {
     pub inner_height: f32,
     pub thickness: f32,
     pub offset: Vec2,
+
+    /// Height of the warning line from the inner bottom.
+    /// Balls above it cause a warning. No warning line if `None`.
+    #[serde(default)]
+    pub warning_line_height: Option<f32>,
+
+    /// Inner outline of the bottle from the top left to the top right via the bottom,
+    /// relative to the inner bottom center. (e.g. many vertices for a round bowl)
+    /// It must be within `inner_width` x `inner_height` and be visible from its center (the mean of the vertices).
+    /// The bottle is a box of `inner_width` x `inner_height` if `None`.
+    #[serde(default)]
+    pub outline: Option<Vec<Vec2>>,
+}
+
+/// A collider placed in the bottle, such as a peg, a shelf or a rotating paddle.
+/// It moves with the bottle.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Reflect)]
+pub struct ObstacleRon {
+    pub shape: ObstacleShapeRon,
+    /// Relative to the inner bottom center of the bottle.
+    pub position: Vec2,
+    /// Radians, counterclockwise.
+    #[serde(default)]
+    pub rotation: f32,
+    /// Radians per second, counterclockwise. Not rotated if 0.
+    /// A rotating obstacle is a body of its own instead of a part of the bottle,
+    /// following the bottle by its velocity.
+    #[serde(default)]
+    pub angular_velocity: f32,
+
+    /// Stretched to the size of the shape.
+    pub image_asset_path: String,
+    /// `bottle_physics` is used if `None`.
+    #[serde(default)]
+    pub physics: Option<RigitBodyRon>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Reflect)]
+pub enum ObstacleShapeRon {
+    Circle {
+        radius: f32,
+    },
+    Rectangle {
+        width: f32,
+        height: f32,
+    },
+    /// A horizontal capsule. `length` is of the straight part.
+    Capsule {
+        length: f32,
+        radius: f32,
+    },
+}
+impl ObstacleShapeRon {
+    /// Size of the bounding box without rotation.
+    pub fn size(&self) -> Vec2 {
+        match *self {
+            Self::Circle { radius } => Vec2::splat(radius * 2.),
+            Self::Rectangle { width, height } => Vec2::new(width, height),
+            Self::Capsule { length, radius } => Vec2::new(length + radius * 2., radius * 2.),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -136,6 +319,18 @@ pub struct ManualViewRon {
     pub width: f32,
     pub height: f32,
 }
+/// Upcoming balls after the next one. Laid out under the hold view.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Reflect)]
+pub struct PreviewViewRon {
+    pub bg_image_asset_path: String,
+    pub border_width: f32,
+    pub font_color: Color,
+    pub width: f32,
+    pub height: f32,
+    /// Number of balls shown.
+    pub count: usize,
+}
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[derive(Reflect)]
 pub struct PopupViewRon {
@@ -148,6 +343,9 @@ pub struct PopupViewRon {
 #[derive(Reflect)]
 pub struct UiRon {
     pub hold_view: HoldViewRon,
+    /// No preview if `None`.
+    #[serde(default)]
+    pub preview_view: Option<PreviewViewRon>,
     pub score_view: ScoreViewRon,
     pub manual_view: ManualViewRon,
     pub view_margin_left: f32,
@@ -162,6 +360,18 @@ pub struct SoundRon {
     pub bgm_scale: f32,
     pub se_combine_asset_path: String,
     pub se_combine_scale: f32,
+    /// Played when a warning starts.
+    #[serde(default)]
+    pub se_warning_asset_path: Option<String>,
+    #[serde(default = "SoundRon::get_default_se_warning_scale")]
+    pub se_warning_scale: f32,
+    /// Playback speed of the BGM during a warning.
+    #[serde(default = "SoundRon::get_default_bgm_warning_speed")]
+    pub bgm_warning_speed: f32,
+}
+impl SoundRon {
+    fn get_default_se_warning_scale() -> f32 { 1.0 }
+    fn get_default_bgm_warning_speed() -> f32 { 1.0 }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -193,6 +403,23 @@ pub struct Area {
     pub max_y: f32,
 }
 
+/// Which balls out of `area` make the game over.
+/// All balls count by default.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[derive(Reflect)]
+pub struct OverflowRon {
+    /// Balls dropped and not touching anything yet don't count.
+    #[serde(default)]
+    pub ignore_dropping: bool,
+    /// Balls growing after spawned don't count.
+    #[serde(default)]
+    pub ignore_growing: bool,
+    /// Balls above `area` count only after they have been at rest for this long.
+    /// Balls beside or below it always count because they keep falling.
+    #[serde(default)]
+    pub settle_ms: u32,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[derive(Reflect)]
 pub struct OtherParamRon {
@@ -210,6 +437,14 @@ pub struct OtherParamRon {
     pub shake_k: f32, // max move is about 0.4 * shake_k
     #[serde(default = "OtherParamRon::get_default_playing_cam_offset")]
     pub playing_cam_offset: Vec2,
+    /// Seconds a ball can stay out of `area` before the game is over.
+    #[serde(default)]
+    pub game_over_grace_sec: f32,
+    #[serde(default)]
+    pub overflow: OverflowRon,
+    /// Duration of a time attack game.
+    #[serde(default = "OtherParamRon::get_default_time_attack_sec")]
+    pub time_attack_sec: f32,
 }
 impl Default for OtherParamRon {
     fn default() -> Self {
@@ -226,6 +461,9 @@ impl Default for OtherParamRon {
             max_velocity: 3000.,
             shake_k: 24. / 0.4,
             playing_cam_offset: Vec2::new(100., 0.),
+            game_over_grace_sec: 0.,
+            overflow: OverflowRon::default(),
+            time_attack_sec: 120.,
         }
     }
 }
@@ -244,6 +482,163 @@ impl OtherParamRon {
     fn get_default_max_velocity() -> f32 { 3000. }
     fn get_default_shake_k() -> f32 { 24. / 0.4 }
     fn get_default_playing_cam_offset() -> Vec2 { Vec2::new(100., 0.) }
+    fn get_default_time_attack_sec() -> f32 { 120. }
+}
+
+/// How levels of next balls are picked.
+/// The default is uniform among levels from 1 to `drop_ball_level_max`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[derive(Reflect)]
+pub struct NextBallRon {
+    /// Weights of levels from 1 to `drop_ball_level_max`. Empty means all 1.
+    #[serde(default)]
+    pub weights: Vec<u32>,
+    /// Picks from a shuffled bag which has `weights[i]` balls of level `i + 1`.
+    /// A new bag is added when it runs out.
+    #[serde(default)]
+    pub bag: bool,
+    /// The same level is not picked more than this times in a row.
+    /// With `bag`, it is ignored when only the same level is left in the bag.
+    #[serde(default)]
+    pub max_repeat: Option<u32>,
+}
+
+/// Rules of holding balls.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Reflect)]
+pub struct HoldRon {
+    /// Number of balls which can be held. 0 disables holding.
+    /// With more than 1 slot, the ball held first comes back first.
+    #[serde(default = "HoldRon::get_default_slots")]
+    pub slots: usize,
+    /// After holding, holding is locked until the next drop.
+    #[serde(default)]
+    pub once_per_drop: bool,
+    /// Number of holds in a game. Unlimited if `None`.
+    #[serde(default)]
+    pub max_holds: Option<u32>,
+}
+impl Default for HoldRon {
+    fn default() -> Self {
+        Self {
+            slots: 1,
+            once_per_drop: false,
+            max_holds: None,
+        }
+    }
+}
+impl HoldRon {
+    fn get_default_slots() -> usize { 1 }
+}
+
+/// Rules of shaking the bottle.
+/// The bottle moves up to about `0.4 * physics.shake_k` by a shake of length 1.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Reflect)]
+pub struct ShakeRon {
+    /// Seconds of a shake.
+    #[serde(default = "ShakeRon::get_default_duration_sec")]
+    pub duration_sec: f32,
+    /// Max tilt in radians by a horizontal shake of length 1. Not tilted if 0.
+    #[serde(default)]
+    pub tilt: f32,
+    /// Seconds after a shake until the next one.
+    #[serde(default)]
+    pub cooldown_sec: f32,
+    /// Number of shakes in a game. Unlimited if `None`.
+    #[serde(default)]
+    pub max_shakes: Option<u32>,
+}
+impl Default for ShakeRon {
+    fn default() -> Self {
+        Self {
+            duration_sec: 1.0,
+            tilt: 0.,
+            cooldown_sec: 0.,
+            max_shakes: None,
+        }
+    }
+}
+impl ShakeRon {
+    fn get_default_duration_sec() -> f32 { 1.0 }
+}
+
+/// Points of a game.
+/// The flood mode doesn't use it because its score is the number of balls dropped.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[derive(Reflect)]
+pub struct ScoringRon {
+    /// Points for combining 2 balls of each level from 1. Empty means `level * level`.
+    #[serde(default)]
+    pub combine_points: Vec<u32>,
+    /// Points for dropping a ball.
+    #[serde(default)]
+    pub drop_points: u32,
+    /// Added to `combine_points` when 2 balls of the max level are combined (and disappear).
+    #[serde(default)]
+    pub max_level_bonus: u32,
+    /// Added when the game ends.
+    #[serde(default)]
+    pub end_bonus: EndBonusRon,
+}
+
+/// Bonus for balls left in the bottle at the end of a game.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[derive(Reflect)]
+pub struct EndBonusRon {
+    /// Points for each ball.
+    #[serde(default)]
+    pub per_ball: u32,
+    /// Points multiplied by the level of each ball.
+    #[serde(default)]
+    pub per_level: u32,
+}
+
+/// When combines are chained.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Reflect)]
+pub enum ChainRon {
+    /// A combine within the seconds after the previous one is chained.
+    TimeWindow(f32),
+    /// Combines until the next drop are chained.
+    SameDrop,
+}
+
+/// Scoring of chained combines.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Reflect)]
+pub struct ComboRon {
+    #[serde(default = "ComboRon::get_default_chain")]
+    pub chain: ChainRon,
+    /// Score multipliers of the 1st, 2nd, ... combines in a chain.
+    /// The last one is used for longer chains. Empty means no bonus.
+    #[serde(default)]
+    pub multipliers: Vec<f32>,
+}
+impl Default for ComboRon {
+    fn default() -> Self {
+        Self {
+            chain: Self::get_default_chain(),
+            multipliers: vec![],
+        }
+    }
+}
+impl ComboRon {
+    fn get_default_chain() -> ChainRon { ChainRon::TimeWindow(1.0) }
+}
+
+/// A goal of the mission mode.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Reflect)]
+pub enum MissionRon {
+    /// Make a ball of the level by combining.
+    MakeBall(usize),
+    /// Reach the score.
+    Score(u32),
+    /// Combine balls the number of times.
+    Combine(u32),
+    /// Have fewer balls than this in the bottle when the other missions are met.
+    FewerBallsThan(usize),
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -254,8 +649,20 @@ pub struct GameRon {
     #[serde(default)]
     pub effects: Vec<EffectRon>,
     pub drop_ball_level_max: usize,
+    #[serde(default)]
+    pub next_ball: NextBallRon,
+    #[serde(default)]
+    pub hold: HoldRon,
+    #[serde(default)]
+    pub shake: ShakeRon,
+    #[serde(default)]
+    pub scoring: ScoringRon,
+    #[serde(default)]
+    pub combo: ComboRon,
     pub player: PlayerRon,
     pub bottle: BottleRon,
+    #[serde(default)]
+    pub obstacles: Vec<ObstacleRon>,
     pub background: BackgroundRon,
     pub sounds: SoundRon,
     pub ui: UiRon,
@@ -263,4 +670,30 @@ pub struct GameRon {
     pub bottle_physics: RigitBodyRon,
     #[serde(default)]
     pub physics: OtherParamRon,
+    /// Goals of the mission mode. All of them must be met to clear.
+    #[serde(default)]
+    pub missions: Vec<MissionRon>,
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inner_radius_of_rounded_polygon_reaches_edges() {
+        let square = BallShapeRon::RoundedPolygon {
+            vertices: vec![Vec2::new(-0.5, -0.5), Vec2::new(0.5, -0.5), Vec2::new(0.5, 0.5), Vec2::new(-0.5, 0.5)],
+            radius: 0.,
+        };
+        assert!((square.inner_radius() - 0.5).abs() < 1e-6);
+
+        let triangle = BallShapeRon::RoundedPolygon {
+            vertices: vec![Vec2::new(0., 0.8), Vec2::new(-0.6, -0.4), Vec2::new(0.6, -0.4)],
+            radius: 0.1,
+        };
+        // The nearest edges are the slopes, nearer than the bottom (0.4).
+        let slope = 0.48 / 1.8_f32.sqrt();
+        assert!((triangle.inner_radius() - (slope + 0.1)).abs() < 1e-6, "{}", triangle.inner_radius());
+    }
 }
@@ -1,4 +1,10 @@
 mod game_ron;
+mod list_ron;
+mod puzzle_ron;
+mod validation;
 
 
 pub use game_ron::*;
+pub use list_ron::*;
+pub use puzzle_ron::*;
+pub use validation::*;
@@ -0,0 +1,27 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::MissionRon;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Reflect)]
+pub struct PuzzleBallRon {
+    pub level: usize,
+    /// Center of the ball from the center of the inner bottom of the bottle.
+    pub position: Vec2,
+}
+
+/// A puzzle of the puzzle mode. It can be played with any `GameRon` which has enough balls.
+/// Puzzles are listed in a `ListRon`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Reflect)]
+#[derive(Asset)]
+pub struct PuzzleRon {
+    /// Balls in the bottle at the start.
+    #[serde(default)]
+    pub balls: Vec<PuzzleBallRon>,
+    /// Levels of balls to drop in order. The game is over when they run out.
+    pub next_balls: Vec<usize>,
+    /// Missions to clear the puzzle.
+    pub goal: Vec<MissionRon>,
+}
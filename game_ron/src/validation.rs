@@ -0,0 +1,548 @@
+use std::fmt;
+
+use bevy::math::Vec2;
+
+use crate::*;
+
+/// An invalid value found in a `GameRon`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameRonError {
+    /// Where the value is. e.g. `balls[2].effect_index`
+    pub field: String,
+    pub message: String,
+}
+
+impl GameRonError {
+    fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for GameRonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+impl GameRon {
+    /// Checks values which can't be checked by deserializing.
+    /// Asset paths are not checked. See `validate_asset_paths()`.
+    pub fn validate(&self) -> Vec<GameRonError> {
+        let mut errors = vec![];
+
+        if self.balls.is_empty() {
+            errors.push(GameRonError::new("balls", "must have at least 1 element."));
+        }
+        if !(1..=self.balls.len()).contains(&self.drop_ball_level_max) {
+            errors.push(GameRonError::new("drop_ball_level_max",
+                format!("must be in [1, {}] (the number of balls), but {}.",
+                    self.balls.len(), self.drop_ball_level_max)));
+        }
+
+        let next_ball = &self.next_ball;
+        if !next_ball.weights.is_empty() {
+            if next_ball.weights.len() != self.drop_ball_level_max {
+                errors.push(GameRonError::new("next_ball.weights",
+                    format!("must have {} (drop_ball_level_max) elements, but {}.",
+                        self.drop_ball_level_max, next_ball.weights.len())));
+            }
+            if next_ball.weights.iter().all(|w| *w == 0) {
+                errors.push(GameRonError::new("next_ball.weights",
+                    "must have at least 1 positive weight."));
+            }
+        }
+        if let Some(max_repeat) = next_ball.max_repeat {
+            let levels = if next_ball.weights.is_empty() {
+                self.drop_ball_level_max
+            } else {
+                next_ball.weights.iter().filter(|w| **w > 0).count()
+            };
+            if max_repeat == 0 {
+                errors.push(GameRonError::new("next_ball.max_repeat", "must be positive."));
+            } else if levels < 2 {
+                errors.push(GameRonError::new("next_ball.max_repeat",
+                    "needs at least 2 levels which can be picked."));
+            }
+        }
+
+        for (i, ball) in self.balls.iter().enumerate() {
+            if ball.physics_radius <= 0. {
+                errors.push(GameRonError::new(format!("balls[{}].physics_radius", i),
+                    format!("must be positive, but {}.", ball.physics_radius)));
+            }
+            validate_ball_shape(&format!("balls[{}].shape", i), &ball.shape, &mut errors);
+            validate_ball_physics(&format!("balls[{}].physics", i), &ball.physics, &mut errors);
+            if let Some(idx) = ball.effect_index {
+                if idx >= self.effects.len() {
+                    errors.push(GameRonError::new(format!("balls[{}].effect_index", i),
+                        format!("must be less than {} (the number of effects), but {}.",
+                            self.effects.len(), idx)));
+                }
+            }
+        }
+
+        for (i, effect) in self.effects.iter().enumerate() {
+            match effect {
+                EffectRon::Scattering(s) => {
+                    for (name, linear) in [
+                        ("alpha", &s.alpha),
+                        ("red", &s.red),
+                        ("green", &s.green),
+                        ("blue", &s.blue),
+                    ] {
+                        if linear.0.is_empty() {
+                            errors.push(GameRonError::new(format!("effects[{}].{}", i, name),
+                                "must have at least 1 element."));
+                        }
+                    }
+                },
+            }
+        }
+
+        if let Some(height) = self.bottle.warning_line_height {
+            if !(0. ..=self.bottle.inner_height).contains(&height) {
+                errors.push(GameRonError::new("bottle.warning_line_height",
+                    format!("must be in [0, {}] (bottle.inner_height), but {}.",
+                        self.bottle.inner_height, height)));
+            }
+        }
+        if let Some(outline) = &self.bottle.outline {
+            let half_width = self.bottle.inner_width / 2.;
+            if outline.len() < 3 {
+                errors.push(GameRonError::new("bottle.outline",
+                    format!("needs 3 or more vertices, but {}.", outline.len())));
+            } else if outline[0].x >= outline[outline.len() - 1].x {
+                errors.push(GameRonError::new("bottle.outline",
+                    "must start from the left and end at the right."));
+            } else if !is_visible_from_center(outline) {
+                errors.push(GameRonError::new("bottle.outline",
+                    "must be visible from its center (the mean of the vertices)."));
+            }
+            for (i, v) in outline.iter().enumerate() {
+                if !(-half_width..=half_width).contains(&v.x) || !(0. ..=self.bottle.inner_height).contains(&v.y) {
+                    errors.push(GameRonError::new(format!("bottle.outline[{}]", i),
+                        format!("must be in [{}, {}] x [0, {}] (bottle.inner_width and inner_height), but ({}, {}).",
+                            -half_width, half_width, self.bottle.inner_height, v.x, v.y)));
+                }
+            }
+        }
+        for (i, obstacle) in self.obstacles.iter().enumerate() {
+            let size = obstacle.shape.size();
+            let is_valid = match obstacle.shape {
+                ObstacleShapeRon::Capsule { length, .. } => length >= 0. && size.y > 0.,
+                _ => size.x > 0. && size.y > 0.,
+            };
+            if !is_valid {
+                errors.push(GameRonError::new(format!("obstacles[{}].shape", i),
+                    format!("must have a positive size, but {:?}.", obstacle.shape)));
+            }
+        }
+        if self.shake.duration_sec <= 0. {
+            errors.push(GameRonError::new("shake.duration_sec",
+                format!("must be positive, but {}.", self.shake.duration_sec)));
+        }
+        if self.shake.cooldown_sec < 0. {
+            errors.push(GameRonError::new("shake.cooldown_sec",
+                format!("must not be negative, but {}.", self.shake.cooldown_sec)));
+        }
+        if self.physics.game_over_grace_sec < 0. {
+            errors.push(GameRonError::new("physics.game_over_grace_sec",
+                format!("must not be negative, but {}.", self.physics.game_over_grace_sec)));
+        }
+        if self.physics.time_attack_sec <= 0. {
+            errors.push(GameRonError::new("physics.time_attack_sec",
+                format!("must be positive, but {}.", self.physics.time_attack_sec)));
+        }
+
+        let combine_points = &self.scoring.combine_points;
+        if !combine_points.is_empty() && combine_points.len() != self.balls.len() {
+            errors.push(GameRonError::new("scoring.combine_points",
+                format!("must have {} (the number of balls) elements, but {}.",
+                    self.balls.len(), combine_points.len())));
+        }
+
+        if let ChainRon::TimeWindow(sec) = self.combo.chain {
+            if sec <= 0. {
+                errors.push(GameRonError::new("combo.chain",
+                    format!("time window must be positive, but {}.", sec)));
+            }
+        }
+        for (i, m) in self.combo.multipliers.iter().enumerate() {
+            if *m < 0. {
+                errors.push(GameRonError::new(format!("combo.multipliers[{}]", i),
+                    format!("must not be negative, but {}.", m)));
+            }
+        }
+
+        if let Some(preview) = &self.ui.preview_view {
+            if preview.count == 0 {
+                errors.push(GameRonError::new("ui.preview_view.count", "must be positive."));
+            }
+        }
+
+        validate_missions("missions", &self.missions, self.balls.len(), &mut errors);
+
+        let Area { min_x, max_x, min_y, max_y } = self.physics.area;
+        if min_x >= max_x {
+            errors.push(GameRonError::new("physics.area",
+                format!("min_x must be less than max_x, but {} >= {}.", min_x, max_x)));
+        }
+        if min_y >= max_y {
+            errors.push(GameRonError::new("physics.area",
+                format!("min_y must be less than max_y, but {} >= {}.", min_y, max_y)));
+        }
+
+        errors
+    }
+
+    /// Checks whether all asset paths exist.
+    pub fn validate_asset_paths(&self, exists: impl Fn(&str) -> bool) -> Vec<GameRonError> {
+        self.asset_paths().into_iter()
+            .filter(|(_, path)| !exists(path))
+            .map(|(field, path)| GameRonError::new(field,
+                format!("`{}` does not exist.", path)))
+            .collect()
+    }
+
+    /// (field, asset path) of all assets.
+    pub fn asset_paths(&self) -> Vec<(String, &str)> {
+        let mut paths = vec![];
+        for (i, ball) in self.balls.iter().enumerate() {
+            paths.push((format!("balls[{}].image_asset_path", i), ball.image_asset_path.as_str()));
+        }
+        for (i, effect) in self.effects.iter().enumerate() {
+            match effect {
+                EffectRon::Scattering(s) => {
+                    for (j, p) in s.image_asset_paths.iter().enumerate() {
+                        paths.push((format!("effects[{}].image_asset_paths[{}]", i, j), p.as_str()));
+                    }
+                },
+            }
+        }
+        paths.extend([
+            ("player.image_asset_path", &self.player.image_asset_path),
+            ("bottle.fg_image_asset_path", &self.bottle.fg_image_asset_path),
+            ("bottle.bg_image_asset_path", &self.bottle.bg_image_asset_path),
+            ("background.bg_image_asset_path", &self.background.bg_image_asset_path),
+            ("sounds.bgm_asset_path", &self.sounds.bgm_asset_path),
+            ("sounds.se_combine_asset_path", &self.sounds.se_combine_asset_path),
+            ("ui.hold_view.bg_image_asset_path", &self.ui.hold_view.bg_image_asset_path),
+            ("ui.score_view.bg_image_asset_path", &self.ui.score_view.bg_image_asset_path),
+            ("ui.manual_view.bg_image_asset_path", &self.ui.manual_view.bg_image_asset_path),
+            ("ui.popup.bg_image_asset_path", &self.ui.popup.bg_image_asset_path),
+        ].map(|(field, path)| (field.to_string(), path.as_str())));
+        if let Some(preview) = &self.ui.preview_view {
+            paths.push(("ui.preview_view.bg_image_asset_path".to_string(), preview.bg_image_asset_path.as_str()));
+        }
+        if let Some(path) = &self.sounds.se_warning_asset_path {
+            paths.push(("sounds.se_warning_asset_path".to_string(), path.as_str()));
+        }
+        for (i, obstacle) in self.obstacles.iter().enumerate() {
+            paths.push((format!("obstacles[{}].image_asset_path", i), obstacle.image_asset_path.as_str()));
+        }
+        paths
+    }
+}
+
+/// Whether all edges of the closed polygon turn the same way around the mean of the vertices.
+/// The polygon is filled by triangles from the center.
+fn is_visible_from_center(polygon: &[Vec2]) -> bool {
+    let center = polygon.iter().copied().sum::<Vec2>() / polygon.len() as f32;
+    let crosses = polygon.iter().zip(polygon.iter().cycle().skip(1))
+        .map(|(v0, v1)| (*v0 - center).perp_dot(*v1 - center))
+        .collect::<Vec<_>>();
+    crosses.iter().all(|c| *c > 0.) || crosses.iter().all(|c| *c < 0.)
+}
+
+/// Checks a ball shape of `field`.
+fn validate_ball_shape(field: &str, shape: &BallShapeRon, errors: &mut Vec<GameRonError>) {
+    match shape {
+        BallShapeRon::Circle => {},
+        BallShapeRon::Ellipse { width, height, vertices } => {
+            if *width <= 0. || *height <= 0. {
+                errors.push(GameRonError::new(field,
+                    format!("width and height must be positive, but {} and {}.", width, height)));
+            }
+            if *vertices < 3 {
+                errors.push(GameRonError::new(field,
+                    format!("vertices must be 3 or more, but {}.", vertices)));
+            }
+        },
+        BallShapeRon::RoundedPolygon { vertices, radius } => {
+            if vertices.len() < 3 {
+                errors.push(GameRonError::new(field,
+                    format!("needs 3 or more vertices, but {}.", vertices.len())));
+            }
+            if *radius < 0. {
+                errors.push(GameRonError::new(field,
+                    format!("radius must not be negative, but {}.", radius)));
+            }
+        },
+        BallShapeRon::Capsule { length, radius } => {
+            if *length < 0. || *radius <= 0. {
+                errors.push(GameRonError::new(field,
+                    format!("length must not be negative and radius must be positive, but {} and {}.", length, radius)));
+            }
+        },
+        BallShapeRon::Circles(circles) => {
+            if circles.is_empty() {
+                errors.push(GameRonError::new(field, "needs at least 1 circle."));
+            }
+            if let Some((_, r)) = circles.iter().find(|(_, r)| *r <= 0.) {
+                errors.push(GameRonError::new(field,
+                    format!("radius of circles must be positive, but {}.", r)));
+            }
+        },
+    }
+    // Other parts treat `physics_radius` as the size of the ball.
+    let outer_radius = shape.outer_radius();
+    if outer_radius > 1. + f32::EPSILON * 4. {
+        errors.push(GameRonError::new(field,
+            format!("must be within physics_radius (1.0), but reaches {}.", outer_radius)));
+    }
+}
+
+/// Checks per-level physical properties of `field`.
+fn validate_ball_physics(field: &str, physics: &BallPhysicsRon, errors: &mut Vec<GameRonError>) {
+    let positives = [
+        ("density", physics.density),
+        ("mass", physics.mass),
+    ];
+    let non_negatives = [
+        ("friction.dynamic_coef", physics.friction.as_ref().map(|f| f.dynamic_coef)),
+        ("friction.static_coef", physics.friction.as_ref().map(|f| f.static_coef)),
+        ("restitution.coef", physics.restitution.as_ref().map(|r| r.coef)),
+        ("linear_damping", physics.linear_damping),
+        ("angular_damping", physics.angular_damping),
+    ];
+    for (name, v) in positives {
+        if let Some(v) = v.filter(|v| *v <= 0.) {
+            errors.push(GameRonError::new(format!("{}.{}", field, name),
+                format!("must be positive, but {}.", v)));
+        }
+    }
+    for (name, v) in non_negatives {
+        if let Some(v) = v.filter(|v| *v < 0.) {
+            errors.push(GameRonError::new(format!("{}.{}", field, name),
+                format!("must not be negative, but {}.", v)));
+        }
+    }
+    if physics.density.is_some() && physics.mass.is_some() {
+        errors.push(GameRonError::new(field, "set either density or mass."));
+    }
+}
+
+/// Checks missions of `field`. `ball_level_max` is the number of balls.
+fn validate_missions(field: &str, missions: &[MissionRon], ball_level_max: usize, errors: &mut Vec<GameRonError>) {
+    for (i, mission) in missions.iter().enumerate() {
+        match mission {
+            MissionRon::MakeBall(level) => {
+                if !(1..=ball_level_max).contains(level) {
+                    errors.push(GameRonError::new(format!("{}[{}]", field, i),
+                        format!("level must be in [1, {}] (the number of balls), but {}.",
+                            ball_level_max, level)));
+                }
+            },
+            MissionRon::FewerBallsThan(n) => {
+                if *n == 0 {
+                    errors.push(GameRonError::new(format!("{}[{}]", field, i),
+                        "number of balls must be positive."));
+                }
+            },
+            MissionRon::Score(_) | MissionRon::Combine(_) => {},
+        }
+    }
+}
+
+impl PuzzleRon {
+    /// Checks values which don't depend on a `GameRon`.
+    pub fn validate(&self) -> Vec<GameRonError> {
+        let mut errors = vec![];
+
+        if self.next_balls.is_empty() {
+            errors.push(GameRonError::new("next_balls", "must have at least 1 element."));
+        }
+        if self.goal.is_empty() {
+            errors.push(GameRonError::new("goal", "must have at least 1 element."));
+        }
+        validate_missions("goal", &self.goal, usize::MAX, &mut errors);
+        for (i, ball) in self.balls.iter().enumerate() {
+            if ball.level == 0 {
+                errors.push(GameRonError::new(format!("balls[{}].level", i), "must be positive."));
+            }
+        }
+        for (i, level) in self.next_balls.iter().enumerate() {
+            if *level == 0 {
+                errors.push(GameRonError::new(format!("next_balls[{}]", i), "must be positive."));
+            }
+        }
+
+        errors
+    }
+
+    /// Checks whether the puzzle can be played with `game_ron`.
+    pub fn validate_with(&self, game_ron: &GameRon) -> Vec<GameRonError> {
+        let mut errors = self.validate();
+        let ball_level_max = game_ron.balls.len();
+        let level_error = |field: String, level: usize| GameRonError::new(field,
+            format!("must be in [1, {}] (the number of balls of the game.ron), but {}.",
+                ball_level_max, level));
+
+        for (i, mission) in self.goal.iter().enumerate() {
+            if let MissionRon::MakeBall(level) = *mission {
+                if level > ball_level_max {
+                    errors.push(level_error(format!("goal[{}]", i), level));
+                }
+            }
+        }
+        let half_width = game_ron.bottle.inner_width / 2.;
+        let height = game_ron.bottle.inner_height;
+        for (i, ball) in self.balls.iter().enumerate() {
+            if ball.level > ball_level_max {
+                errors.push(level_error(format!("balls[{}].level", i), ball.level));
+            }
+            let (x, y) = (ball.position.x, ball.position.y);
+            if !(-half_width..=half_width).contains(&x) || !(0. ..=height).contains(&y) {
+                errors.push(GameRonError::new(format!("balls[{}].position", i),
+                    format!("must be inside the bottle ([{}, {}] x [0, {}]), but ({}, {}).",
+                        -half_width, half_width, height, x, y)));
+            }
+        }
+        for (i, level) in self.next_balls.iter().enumerate() {
+            if *level > ball_level_max {
+                errors.push(level_error(format!("next_balls[{}]", i), *level));
+            }
+        }
+
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kao_game_ron() -> GameRon {
+        ron::from_str(include_str!("../../suika_clone/assets/ron/kao.game.ron"))
+            .expect("kao.game.ron is invalid")
+    }
+
+    fn fields(errors: &[GameRonError]) -> Vec<&str> {
+        errors.iter().map(|e| e.field.as_str()).collect()
+    }
+
+    #[test]
+    fn kao_is_valid() {
+        assert_eq!(kao_game_ron().validate(), vec![]);
+    }
+
+    #[test]
+    fn empty_balls_is_error() {
+        let mut game_ron = kao_game_ron();
+        game_ron.balls.clear();
+        game_ron.drop_ball_level_max = 1;
+        game_ron.missions.clear();
+
+        let errors = game_ron.validate();
+
+        assert!(fields(&errors).contains(&"balls"), "{:?}", errors);
+    }
+
+    #[test]
+    fn too_large_drop_ball_level_max_is_error() {
+        let mut game_ron = kao_game_ron();
+        game_ron.drop_ball_level_max = game_ron.balls.len() + 1;
+
+        let errors = game_ron.validate();
+
+        assert_eq!(fields(&errors), vec!["drop_ball_level_max"]);
+    }
+
+    #[test]
+    fn bad_effect_index_is_error() {
+        let mut game_ron = kao_game_ron();
+        game_ron.balls[2].effect_index = Some(game_ron.effects.len());
+
+        let errors = game_ron.validate();
+
+        assert_eq!(fields(&errors), vec!["balls[2].effect_index"]);
+    }
+
+    #[test]
+    fn empty_linear_is_error() {
+        let mut game_ron = kao_game_ron();
+        let EffectRon::Scattering(s) = &mut game_ron.effects[1];
+        s.red.0.clear();
+
+        let errors = game_ron.validate();
+
+        assert_eq!(fields(&errors), vec!["effects[1].red"]);
+    }
+
+    #[test]
+    fn non_positive_radius_is_error() {
+        let mut game_ron = kao_game_ron();
+        game_ron.balls[0].physics_radius = 0.;
+        game_ron.balls[3].physics_radius = -1.;
+
+        let errors = game_ron.validate();
+
+        assert_eq!(fields(&errors), vec!["balls[0].physics_radius", "balls[3].physics_radius"]);
+    }
+
+    #[test]
+    fn empty_area_is_error() {
+        let mut game_ron = kao_game_ron();
+        game_ron.physics.area.max_x = game_ron.physics.area.min_x;
+        game_ron.physics.area.min_y = game_ron.physics.area.max_y + 1.;
+
+        let errors = game_ron.validate();
+
+        assert_eq!(fields(&errors), vec!["physics.area", "physics.area"]);
+        assert!(errors[0].message.starts_with("min_x"));
+        assert!(errors[1].message.starts_with("min_y"));
+    }
+
+    #[test]
+    fn non_positive_time_attack_sec_is_error() {
+        let mut game_ron = kao_game_ron();
+        game_ron.physics.time_attack_sec = 0.;
+
+        let errors = game_ron.validate();
+
+        assert_eq!(fields(&errors), vec!["physics.time_attack_sec"]);
+    }
+
+    #[test]
+    fn outline_not_visible_from_center_is_error() {
+        let mut game_ron = kao_game_ron();
+        // The bottom is dented up above the center.
+        game_ron.bottle.outline = Some(vec![
+            Vec2::new(-300., 500.), Vec2::new(-300., 0.), Vec2::new(0., 400.),
+            Vec2::new(300., 0.), Vec2::new(300., 500.),
+        ]);
+
+        let errors = game_ron.validate();
+
+        assert_eq!(fields(&errors), vec!["bottle.outline"]);
+
+        game_ron.bottle.outline = Some(vec![
+            Vec2::new(-300., 500.), Vec2::new(-100., 0.), Vec2::new(100., 0.), Vec2::new(300., 500.),
+        ]);
+        assert_eq!(game_ron.validate(), vec![]);
+    }
+
+    #[test]
+    fn missing_asset_path_is_error() {
+        let game_ron = kao_game_ron();
+        let missing = game_ron.balls[1].image_asset_path.clone();
+
+        let errors = game_ron.validate_asset_paths(|path| path != missing
+            && path != game_ron.player.image_asset_path);
+
+        assert_eq!(fields(&errors), vec!["balls[1].image_asset_path", "player.image_asset_path"]);
+    }
+}
@@ -0,0 +1,77 @@
+use clap::Args;
+
+use game_ron::*;
+
+/// Radii are `base_radius * ratio^i` or given by `--radii`.
+/// `effect_index`es, shapes, view offsets and physics of existing balls are kept.
+/// (Shapes and view offsets are relative to the radius.)
+#[derive(Args)]
+pub struct GenBallsArgs {
+    /// Radius of the smallest ball.
+    #[arg(long, required_unless_present = "radii")]
+    base_radius: Option<f32>,
+
+    /// Growth ratio of radii between adjacent levels.
+    #[arg(long, conflicts_with = "radii", default_value_t = 1.25)]
+    ratio: f32,
+
+    /// Number of balls.
+    #[arg(long, conflicts_with = "radii", default_value_t = 11)]
+    count: usize,
+
+    /// Explicit radii. e.g. `--radii 25,31,39.5`
+    #[arg(long, value_delimiter = ',', conflicts_with = "base_radius")]
+    radii: Option<Vec<f32>>,
+
+    /// view_width (and view_height) / physics_radius
+    #[arg(long)]
+    view_ratio: f32,
+
+    /// `{}` or `{:0N}` is replaced with the level (1-origin).
+    /// e.g. `images/kao/kao_{:02}.png`
+    #[arg(long)]
+    image_path: String,
+}
+
+impl GenBallsArgs {
+    fn radii(&self) -> Vec<f32> {
+        match (&self.radii, self.base_radius) {
+            (Some(radii), _) => radii.clone(),
+            (None, Some(base)) => (0..self.count)
+                .map(|i| base * self.ratio.powi(i as i32))
+                .collect(),
+            (None, None) => unreachable!("clap requires one of them"),
+        }
+    }
+}
+
+/// Replaces the first `{...}` in `template` with `level`.
+fn format_image_path(template: &str, level: usize) -> String {
+    let Some(start) = template.find('{') else {
+        return template.to_string();
+    };
+    let Some(len) = template[start..].find('}') else {
+        return template.to_string();
+    };
+    let spec = &template[start+1..start+len];
+    let width = spec.strip_prefix(":0")
+        .and_then(|w| w.parse::<usize>().ok())
+        .unwrap_or(0);
+    format!("{}{:0width$}{}", &template[..start], level, &template[start+len+1..], width = width)
+}
+
+pub fn gen_balls(args: &GenBallsArgs, ron: &mut GameRon) {
+    let balls = args.radii().into_iter().enumerate()
+        .map(|(i, r)| BallLevelSettingRon {
+            physics_radius: r,
+            view_width: r * args.view_ratio,
+            view_height: r * args.view_ratio,
+            image_asset_path: format_image_path(&args.image_path, i + 1),
+            effect_index: ron.balls.get(i).and_then(|b| b.effect_index),
+            shape: ron.balls.get(i).map(|b| b.shape.clone()).unwrap_or_default(),
+            view_offset: ron.balls.get(i).map(|b| b.view_offset).unwrap_or_default(),
+            physics: ron.balls.get(i).map(|b| b.physics.clone()).unwrap_or_default(),
+        })
+        .collect();
+    ron.balls = balls;
+}
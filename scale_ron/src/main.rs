@@ -1,4 +1,4 @@
-use clap::Parser;
+use clap::{Args, Parser, Subcommand};
 
 use std::io;
 use std::io::Read;
@@ -6,20 +6,218 @@ use std::io::Read;
 use game_ron::*;
 use ron::ser::PrettyConfig;
 
+mod gen_balls;
+
+/// Edits a game.ron read from stdin and writes it to stdout.
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
+#[command(args_conflicts_with_subcommands = true)]
 struct Cmd {
+    #[command(subcommand)]
+    command: Option<SubCmd>,
+
+    #[command(flatten)]
+    scale: ScaleArgs,
+}
+
+#[derive(Subcommand)]
+enum SubCmd {
+    /// Replaces `balls` with a generated progression.
+    GenBalls(gen_balls::GenBallsArgs),
+}
+
+/// Scales geometric values.
+/// A section flag without a value uses SCALE.
+#[derive(Args)]
+struct ScaleArgs {
+    #[arg(default_value_t = 1.0)]
     scale: f32,
 
-    #[arg(long)]
-    balls: bool,
+    #[arg(long, num_args = 0..=1, value_name = "SCALE")]
+    balls: Option<Option<f32>>,
 
-    #[arg(long)]
-    bottle: bool,
+    #[arg(long, num_args = 0..=1, value_name = "SCALE")]
+    bottle: Option<Option<f32>>,
 
+    #[arg(long, num_args = 0..=1, value_name = "SCALE")]
+    player: Option<Option<f32>>,
+
+    #[arg(long, num_args = 0..=1, value_name = "SCALE")]
+    ui: Option<Option<f32>>,
+
+    #[arg(long, num_args = 0..=1, value_name = "SCALE")]
+    background: Option<Option<f32>>,
+
+    #[arg(long, num_args = 0..=1, value_name = "SCALE")]
+    physics: Option<Option<f32>>,
+
+    #[arg(long, num_args = 0..=1, value_name = "SCALE")]
+    effects: Option<Option<f32>>,
+
+    /// All sections. Sections given a value are scaled by it.
     #[arg(short, long)]
     all: bool,
 
+    /// Prints changed values instead of the scaled ron.
+    #[arg(long)]
+    dry_run: bool,
+}
+
+impl ScaleArgs {
+    /// Factor of a section. `None` if the section is not scaled.
+    fn factor(&self, section: Option<Option<f32>>) -> Option<f32> {
+        match section {
+            Some(k) => Some(k.unwrap_or(self.scale)),
+            None => self.all.then_some(self.scale),
+        }
+    }
+}
+
+/// Scales values and records their changes.
+#[derive(Default)]
+struct Scaler {
+    changes: Vec<String>,
+}
+
+impl Scaler {
+    fn f32(&mut self, field: &str, v: &mut f32, k: f32) {
+        let old = *v;
+        *v *= k;
+        if old != *v {
+            self.changes.push(format!("{}: {} -> {}", field, old, *v));
+        }
+    }
+
+    fn xy(&mut self, field: &str, x: &mut f32, y: &mut f32, k: f32) {
+        self.f32(&format!("{}.x", field), x, k);
+        self.f32(&format!("{}.y", field), y, k);
+    }
+}
+
+type ScaleSection = fn(&mut Scaler, &mut GameRon, f32);
+
+fn scale_balls(s: &mut Scaler, ron: &mut GameRon, k: f32) {
+    for (i, b) in ron.balls.iter_mut().enumerate() {
+        s.f32(&format!("balls[{}].physics_radius", i), &mut b.physics_radius, k);
+        s.f32(&format!("balls[{}].view_width", i), &mut b.view_width, k);
+        s.f32(&format!("balls[{}].view_height", i), &mut b.view_height, k);
+        if let Some(mass) = &mut b.physics.mass {
+            // Mass is of the area at the same density.
+            s.f32(&format!("balls[{}].physics.mass", i), mass, k * k);
+        }
+    }
+}
+
+fn scale_bottle(s: &mut Scaler, ron: &mut GameRon, k: f32) {
+    let bottle = &mut ron.bottle;
+    s.f32("bottle.inner_width", &mut bottle.inner_width, k);
+    s.f32("bottle.inner_height", &mut bottle.inner_height, k);
+    s.f32("bottle.thickness", &mut bottle.thickness, k);
+    s.xy("bottle.offset", &mut bottle.offset.x, &mut bottle.offset.y, k);
+    if let Some(height) = &mut bottle.warning_line_height {
+        s.f32("bottle.warning_line_height", height, k);
+    }
+    if let Some(outline) = &mut bottle.outline {
+        for (i, v) in outline.iter_mut().enumerate() {
+            s.xy(&format!("bottle.outline[{}]", i), &mut v.x, &mut v.y, k);
+        }
+    }
+    // Obstacles are in the bottle.
+    for (i, obstacle) in ron.obstacles.iter_mut().enumerate() {
+        let field = format!("obstacles[{}]", i);
+        s.xy(&format!("{}.position", field), &mut obstacle.position.x, &mut obstacle.position.y, k);
+        match &mut obstacle.shape {
+            ObstacleShapeRon::Circle { radius } => {
+                s.f32(&format!("{}.shape.radius", field), radius, k);
+            },
+            ObstacleShapeRon::Rectangle { width, height } => {
+                s.f32(&format!("{}.shape.width", field), width, k);
+                s.f32(&format!("{}.shape.height", field), height, k);
+            },
+            ObstacleShapeRon::Capsule { length, radius } => {
+                s.f32(&format!("{}.shape.length", field), length, k);
+                s.f32(&format!("{}.shape.radius", field), radius, k);
+            },
+        }
+    }
+}
+
+fn scale_player(s: &mut Scaler, ron: &mut GameRon, k: f32) {
+    let player = &mut ron.player;
+    s.f32("player.view_width", &mut player.view_width, k);
+    s.f32("player.view_height", &mut player.view_height, k);
+    s.f32("player.offset_x", &mut player.offset_x, k);
+    s.f32("player.offset_y", &mut player.offset_y, k);
+    s.f32("player.speed", &mut player.speed, k);
+}
+
+fn scale_ui(s: &mut Scaler, ron: &mut GameRon, k: f32) {
+    let ui = &mut ron.ui;
+    s.f32("ui.hold_view.width", &mut ui.hold_view.width, k);
+    s.f32("ui.hold_view.height", &mut ui.hold_view.height, k);
+    if let Some(preview) = &mut ui.preview_view {
+        s.f32("ui.preview_view.width", &mut preview.width, k);
+        s.f32("ui.preview_view.height", &mut preview.height, k);
+    }
+    s.f32("ui.score_view.width", &mut ui.score_view.width, k);
+    s.f32("ui.score_view.height", &mut ui.score_view.height, k);
+    s.f32("ui.manual_view.width", &mut ui.manual_view.width, k);
+    s.f32("ui.manual_view.height", &mut ui.manual_view.height, k);
+    s.f32("ui.view_margin_left", &mut ui.view_margin_left, k);
+    s.f32("ui.view_margin_y", &mut ui.view_margin_y, k);
+}
+
+fn scale_background(s: &mut Scaler, ron: &mut GameRon, k: f32) {
+    let offset = &mut ron.background.offset;
+    s.xy("background.offset", &mut offset.x, &mut offset.y, k);
+}
+
+fn scale_physics(s: &mut Scaler, ron: &mut GameRon, k: f32) {
+    let physics = &mut ron.physics;
+    s.f32("physics.gravity", &mut physics.gravity, k);
+    s.f32("physics.max_velocity", &mut physics.max_velocity, k);
+    s.f32("physics.shake_k", &mut physics.shake_k, k);
+    s.f32("physics.area.min_x", &mut physics.area.min_x, k);
+    s.f32("physics.area.max_x", &mut physics.area.max_x, k);
+    s.f32("physics.area.min_y", &mut physics.area.min_y, k);
+    s.f32("physics.area.max_y", &mut physics.area.max_y, k);
+    let cam = &mut physics.playing_cam_offset;
+    s.xy("physics.playing_cam_offset", &mut cam.x, &mut cam.y, k);
+}
+
+fn scale_effects(s: &mut Scaler, ron: &mut GameRon, k: f32) {
+    for (i, effect) in ron.effects.iter_mut().enumerate() {
+        match effect {
+            EffectRon::Scattering(e) => {
+                s.f32(&format!("effects[{}].image_scale", i), &mut e.image_scale, k);
+                s.f32(&format!("effects[{}].velocity.0", i), &mut e.velocity.0, k);
+                s.f32(&format!("effects[{}].velocity.1", i), &mut e.velocity.1, k);
+                let acc = &mut e.accelation;
+                s.xy(&format!("effects[{}].accelation.0", i), &mut acc.0.x, &mut acc.0.y, k);
+                s.xy(&format!("effects[{}].accelation.1", i), &mut acc.1.x, &mut acc.1.y, k);
+            },
+        }
+    }
+}
+
+/// Returns changes of values.
+fn scale(args: &ScaleArgs, ron: &mut GameRon) -> Vec<String> {
+    let mut scaler = Scaler::default();
+    let sections: [(Option<Option<f32>>, ScaleSection); 7] = [
+        (args.balls, scale_balls),
+        (args.bottle, scale_bottle),
+        (args.player, scale_player),
+        (args.ui, scale_ui),
+        (args.background, scale_background),
+        (args.physics, scale_physics),
+        (args.effects, scale_effects),
+    ];
+    for (section, scale) in sections {
+        if let Some(k) = args.factor(section) {
+            scale(&mut scaler, ron, k);
+        }
+    }
+    scaler.changes
 }
 
 fn main() {
@@ -33,24 +231,25 @@ fn main() {
     let mut ron: GameRon = ron::from_str(&buf)
         .expect("");
 
-
-    if args.balls || args.all {
-        for s in ron.balls.iter_mut() {
-            s.physics_radius *= args.scale;
-            s.view_width *= args.scale;
-            s.view_height *= args.scale;
-        }
+    match &args.command {
+        Some(SubCmd::GenBalls(gen_args)) => {
+            gen_balls::gen_balls(gen_args, &mut ron);
+        },
+        None => {
+            let changes = scale(&args.scale, &mut ron);
+            if args.scale.dry_run {
+                for change in changes.iter() {
+                    println!("{}", change);
+                }
+                return;
+            }
+        },
     }
 
-    if args.bottle || args.all {
-        let bottle = &mut ron.bottle;
-
-        bottle.inner_width *= args.scale;
-        bottle.inner_height *= args.scale;
-        bottle.thickness *= args.scale;
+    for e in ron.validate() {
+        eprintln!("warning: {}", e);
     }
 
-
     let out = ron::ser::to_string_pretty(&ron, PrettyConfig::default())
         .expect("Failed to write stdout");
 
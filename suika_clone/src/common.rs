@@ -9,7 +9,10 @@ use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use bevy_pkv::PkvStore;
 
-use crate::game_ron_loadable::get_default_game_ron_name_and_asset_path;
+use crate::game_ron_loadable::{
+    get_default_game_ron_name_and_asset_path,
+    get_default_puzzle_name_and_asset_path,
+};
 
 pub const LOGICAL_WIDTH: f32 = 1280.;
 pub const LOGICAL_HEIGHT: f32 = 840.;
@@ -33,6 +36,7 @@ pub enum GameState {
 #[derive(Resource, Debug, Default)]
 pub struct AppArgs {
     pub force_ron_file: Option<String>,
+    pub replay_file: Option<String>,
 }
 
 
@@ -49,6 +53,18 @@ pub struct Config {
     pub game_ron_name: String,
     pub game_ron_asset_path: String,
 
+    #[serde(default)]
+    pub game_mode: GameMode,
+
+    /// Used only in the puzzle mode.
+    #[serde(default = "Config::get_default_puzzle_name")]
+    pub puzzle_name: String,
+    #[serde(default = "Config::get_default_puzzle_asset_path")]
+    pub puzzle_asset_path: String,
+
+    /// Plays the daily challenge: everybody gets the same balls on the same day.
+    #[serde(default)]
+    pub is_daily: bool,
 }
 impl Default for Config {
     fn default() -> Self {
@@ -59,6 +75,13 @@ impl Default for Config {
 
             game_ron_name: game_ron_name.to_string(),
             game_ron_asset_path: asset_path.to_string(),
+
+            game_mode: default(),
+
+            puzzle_name: Self::get_default_puzzle_name(),
+            puzzle_asset_path: Self::get_default_puzzle_asset_path(),
+
+            is_daily: false,
         }
     }
 }
@@ -68,6 +91,13 @@ fn volume(v: i32, scale: f32) -> Volume {
 const STORE_NAME_CONFIG: &str = "config";
 
 impl Config {
+    fn get_default_puzzle_name() -> String {
+        get_default_puzzle_name_and_asset_path().0.to_string()
+    }
+    fn get_default_puzzle_asset_path() -> String {
+        get_default_puzzle_name_and_asset_path().1.to_string()
+    }
+
     pub fn get_se_volume(&self, scale:f32) -> Volume {
         volume(self.se_volume, scale)
     }
@@ -103,12 +133,79 @@ const STORE_NAME_SCORES: &str = "scores";
 #[derive(Deserialize, Serialize)]
 pub struct Score {
     pub score: u32,
+    /// Seed of the game.
+    #[serde(default)]
+    pub seed: u64,
+    #[serde(default)]
+    pub combo: ComboStats,
 }
 
 impl Score {
-    pub fn new(score: u32) -> Self {
+    pub fn new(score: u32, seed: u64) -> Self {
         Self {
             score,
+            seed,
+            combo: default(),
+        }
+    }
+    pub fn with_combo(mut self, combo: ComboStats) -> Self {
+        self.combo = combo;
+        self
+    }
+}
+
+/// Combo statistics of a game.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Default)]
+#[derive(Reflect)]
+#[derive(Deserialize, Serialize)]
+pub struct ComboStats {
+    /// The longest chain of combines.
+    pub max_chain: u32,
+    /// Number of chains of 2 or more combines.
+    pub chains: u32,
+}
+
+/// Rule to play.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Default)]
+#[derive(Reflect)]
+#[derive(Deserialize, Serialize)]
+pub enum GameMode {
+    #[default]
+    Endless,
+    /// Score as much as possible in a fixed time.
+    TimeAttack,
+    /// Drop as many balls as possible. The score is the number of balls dropped.
+    Flood,
+    /// Meet all missions of the game.ron.
+    Mission,
+    /// Meet the goal of a puzzle with the balls given.
+    Puzzle,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 5] = [
+        GameMode::Endless,
+        GameMode::TimeAttack,
+        GameMode::Flood,
+        GameMode::Mission,
+        GameMode::Puzzle,
+    ];
+
+    pub fn get_str(&self) -> &'static str {
+        match *self {
+            GameMode::Endless => "Endless",
+            GameMode::TimeAttack => "Time Attack",
+            GameMode::Flood => "Flood",
+            GameMode::Mission => "Mission",
+            GameMode::Puzzle => "Puzzle",
+        }
+    }
+
+    /// Label of the score view.
+    pub fn get_score_label(&self) -> &'static str {
+        match *self {
+            GameMode::Flood => "BALLS",
+            _ => "SCORE",
         }
     }
 }
@@ -117,7 +214,12 @@ impl Score {
 #[derive(Reflect)]
 #[derive(Deserialize, Serialize)]
 pub struct GameCond {
-    pub mode: String,
+    pub game_ron_name: String,
+    pub game_mode: GameMode,
+    /// Only in the puzzle mode.
+    pub puzzle_name: Option<String>,
+    /// Date of the daily challenge.
+    pub daily: Option<String>,
     pub app_ver: String,
 }
 
@@ -127,14 +229,68 @@ impl GameCond {
             .expect("Failed to parse app_ver")
     }
 
-    pub fn new(mode: &str) -> Self {
+    pub fn new(game_ron_name: &str, game_mode: GameMode) -> Self {
         Self {
-            mode: mode.to_string(),
+            game_ron_name: game_ron_name.to_string(),
+            game_mode,
+            puzzle_name: None,
+            daily: None,
             app_ver: option_env!("CARGO_PKG_VERSION")
                 .unwrap_or("0.0.0")
                 .to_string(),
         }
     }
+
+    pub fn with_puzzle(mut self, puzzle_name: &str) -> Self {
+        self.puzzle_name = Some(puzzle_name.to_string());
+        self
+    }
+
+    pub fn with_daily(mut self, date: Option<&str>) -> Self {
+        self.daily = date.map(|d| d.to_string());
+        self
+    }
+
+    /// The condition of a game played with `config` in `game_mode`.
+    pub fn from_config(config: &Config, game_mode: GameMode) -> Self {
+        let cond = Self::new(&config.game_ron_name, game_mode);
+        match game_mode {
+            GameMode::Puzzle => cond.with_puzzle(&config.puzzle_name),
+            _ => cond,
+        }
+    }
+
+    /// e.g. `Puzzle (Puzzle 1)`, `Endless Daily 2024-06-01`
+    pub fn get_mode_str(&self) -> String {
+        let mut s = self.game_mode.get_str().to_string();
+        if let Some(puzzle_name) = &self.puzzle_name {
+            s += &format!(" ({})", puzzle_name);
+        }
+        if let Some(date) = &self.daily {
+            s += &format!(" Daily {}", date);
+        }
+        s
+    }
+
+    /// Key of `Scores` per app_ver.
+    pub fn score_key(&self) -> String {
+        let key = self.score_key_without_daily();
+        match &self.daily {
+            Some(date) => format!("{} [Daily {}]", key, date),
+            None => key,
+        }
+    }
+
+    fn score_key_without_daily(&self) -> String {
+        match self.game_mode {
+            // Endless uses only the name so that scores saved before adding game modes are kept.
+            GameMode::Endless => self.game_ron_name.clone(),
+            // A puzzle cleared with any game.ron is cleared.
+            GameMode::Puzzle => format!("[{}: {}]",
+                self.game_mode.get_str(), self.puzzle_name.as_deref().unwrap_or_default()),
+            _ => format!("{} [{}]", self.game_ron_name, self.game_mode.get_str()),
+        }
+    }
 }
 
 
@@ -142,13 +298,13 @@ impl GameCond {
 #[derive(Reflect)]
 #[derive(Deserialize, Serialize)]
 pub struct Scores {
-    /// app_ver -> mode -> scores
+    /// app_ver -> `GameCond::score_key` -> scores
     scores: HashMap<String, HashMap<String, Vec<Score>>>,
 }
 impl Scores {
     pub fn get(&self, key: &GameCond) -> Option<&Vec<Score>> {
         self.scores.get(key.app_ver.as_str())
-            .and_then(|m| m.get(key.mode.as_str()))
+            .and_then(|m| m.get(key.score_key().as_str()))
     }
 
     pub fn get_highest(&self, key: &GameCond) -> Option<&Score> {
@@ -157,18 +313,18 @@ impl Scores {
 
     pub fn push(&mut self, key: &GameCond, score: Score) {
         if let Some(per_mode) = self.scores.get_mut(&key.app_ver) {
-            if let Some(scores) = per_mode.get_mut(&key.mode) {
+            if let Some(scores) = per_mode.get_mut(&key.score_key()) {
                 scores.push(score);
                 scores.sort_by(|l, r| l.cmp(r).reverse());
                 if scores.len() > SCORE_MAX_ENTRY_PER_SAME_COND {
                     scores.truncate(SCORE_MAX_ENTRY_PER_SAME_COND);
                 }
             } else {
-                per_mode.insert(key.mode.clone(), vec![score]);
+                per_mode.insert(key.score_key(), vec![score]);
             }
         } else {
             let mut m = HashMap::new();
-            m.insert(key.mode.clone(), vec![score]);
+            m.insert(key.score_key(), vec![score]);
             self.scores.insert(key.app_ver.clone(), m);
         }
     }
@@ -195,6 +351,37 @@ pub fn save_scores(
 
 const SCORE_MAX_ENTRY_PER_SAME_COND: usize = 10;
 
+/// Today in UTC (e.g. `2024-06-01`) so that the day changes at the same time all over the world.
+pub fn daily_date_today() -> String {
+    let secs = bevy::utils::SystemTime::now()
+        .duration_since(bevy::utils::SystemTime::UNIX_EPOCH)
+        .expect("The clock is before 1970")
+        .as_secs();
+    date_from_unix_days((secs / (24 * 60 * 60)) as i64)
+}
+
+/// `YYYY-MM-DD` of days since 1970-01-01.
+/// See: http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+pub fn date_from_unix_days(days: i64) -> String {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", y, m, d)
+}
+
+/// Seed of the daily challenge.
+/// FNV-1a is used because it must be the same on any platform and any version.
+pub fn daily_seed(date: &str, game_ron_name: &str) -> u64 {
+    format!("{}/{}", date, game_ron_name).bytes()
+        .fold(0xcbf29ce484222325, |h, b| (h ^ b as u64).wrapping_mul(0x100000001b3))
+}
+
 
 
 
@@ -309,3 +496,43 @@ impl GpKbInput {
         }
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn today_is_formatted() {
+        let today = daily_date_today();
+        assert_eq!(today.len(), 10);
+        assert_eq!(today.match_indices('-').map(|(i, _)| i).collect::<Vec<_>>(), vec![4, 7]);
+    }
+
+    #[test]
+    fn dates_from_unix_days() {
+        assert_eq!(date_from_unix_days(0), "1970-01-01");
+        assert_eq!(date_from_unix_days(19782), "2024-02-29");
+        assert_eq!(date_from_unix_days(19875), "2024-06-01");
+        assert_eq!(date_from_unix_days(-1), "1969-12-31");
+    }
+
+    #[test]
+    fn daily_seed_depends_on_date_and_game_ron() {
+        let seed = daily_seed("2024-06-01", "Kao");
+        assert_eq!(seed, daily_seed("2024-06-01", "Kao"));
+        assert_ne!(seed, daily_seed("2024-06-02", "Kao"));
+        assert_ne!(seed, daily_seed("2024-06-01", "Default"));
+    }
+
+    #[test]
+    fn daily_scores_are_separated() {
+        let cond = GameCond::new("Kao", GameMode::Endless);
+        let daily = cond.clone().with_daily(Some("2024-06-01"));
+        let mut scores = Scores::default();
+        scores.push(&daily, Score::new(100, daily_seed("2024-06-01", "Kao")));
+
+        assert!(scores.get_highest(&cond).is_none());
+        assert_eq!(scores.get_highest(&daily).map(|s| s.score), Some(100));
+    }
+}
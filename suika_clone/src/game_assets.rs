@@ -13,16 +13,26 @@ pub struct BallLevelDef {
     pub h_image: Handle<Image>,
 
     pub effect_index: Option<usize>,
+
+    /// Relative to `physics_radius`.
+    pub shape: BallShapeRon,
+    pub view_offset: Vec2,
+
+    /// Overrides of `GameAssets::ball_physics`.
+    pub physics: BallPhysicsRon,
 }
 
 impl BallLevelDef {
-    pub fn create_with_loading(n: &BallLevelSettingRon, asset_server: &AssetServer,) -> Self {
+    pub fn create_with_loading(n: &BallLevelSettingRon, loader: &impl HandleLoader) -> Self {
         Self {
             physics_radius: n.physics_radius,
             view_width: n.view_width,
             view_height: n.view_height,
-            h_image: asset_server.load(&n.image_asset_path),
+            h_image: loader.load(&n.image_asset_path),
             effect_index: n.effect_index,
+            shape: n.shape.clone(),
+            view_offset: n.view_offset * n.physics_radius,
+            physics: n.physics.clone(),
         }
     }
 }
@@ -109,11 +119,11 @@ pub enum EffectDef {
 }
 
 impl EffectDef {
-    pub fn create_with_loading(ron: &EffectRon, asset_server: &AssetServer) -> Self {
+    pub fn create_with_loading(ron: &EffectRon, loader: &impl HandleLoader) -> Self {
         match ron {
             game_ron::EffectRon::Scattering(s) => {
                 let h_images = s.image_asset_paths.iter().map(|p|
-                    asset_server.load(p)
+                    loader.load(p)
                 ).collect();
 
                 Self::Scattering(effects::Scattering{
@@ -154,13 +164,13 @@ pub struct PlayerDef {
     pub speed: f32,
 }
 impl PlayerDef {
-    pub fn create_with_loading(ron: &PlayerRon, asset_server: &AssetServer) -> Self {
+    pub fn create_with_loading(ron: &PlayerRon, loader: &impl HandleLoader) -> Self {
         Self {
             view_width: ron.view_width,
             view_height: ron.view_height,
             offset_x: ron.offset_x,
             offset_y: ron.offset_y,
-            h_image: asset_server.load(&ron.image_asset_path),
+            h_image: loader.load(&ron.image_asset_path),
             guide_color: ron.guide_color,
             speed: ron.speed,
         }
@@ -178,17 +188,40 @@ pub struct BottleDef {
     pub thickness: f32,
 
     pub offset: Vec2,
+
+    /// Height from the inner bottom.
+    pub warning_line_height: Option<f32>,
+
+    /// Relative to the inner bottom center.
+    pub outline: Option<Vec<Vec2>>,
 }
 impl BottleDef {
-    pub fn create_with_loading(ron: &BottleRon, asset_server: &AssetServer) -> Self {
+    pub fn create_with_loading(ron: &BottleRon, loader: &impl HandleLoader) -> Self {
         Self {
-            h_fg_image: asset_server.load(&ron.fg_image_asset_path),
-            h_bg_image: asset_server.load(&ron.bg_image_asset_path),
+            h_fg_image: loader.load(&ron.fg_image_asset_path),
+            h_bg_image: loader.load(&ron.bg_image_asset_path),
             image_border: ron.image_border,
             inner_width: ron.inner_width,
             inner_height: ron.inner_height,
             thickness: ron.thickness,
             offset: ron.offset,
+            warning_line_height: ron.warning_line_height,
+            outline: ron.outline.clone(),
+        }
+    }
+
+    /// The outline relative to the bottle center.
+    pub fn local_outline(&self) -> Option<Vec<Vec2>> {
+        let bottom_c = self.inner_bottom_center() - self.offset;
+        self.outline.as_ref()
+            .map(|outline| outline.iter().map(|v| *v + bottom_c).collect())
+    }
+
+    /// X range of the opening in the world.
+    pub fn opening_x(&self) -> (f32, f32) {
+        match self.outline.as_deref() {
+            Some([first, .., last]) => (first.x + self.offset.x, last.x + self.offset.x),
+            _ => (-self.inner_width / 2. + self.offset.x, self.inner_width / 2. + self.offset.x),
         }
     }
 
@@ -212,6 +245,9 @@ impl BottleDef {
             outer_size.y,
         )
     }
+    pub fn inner_bottom_center(&self) -> Vec2 {
+        self.offset - Vec2::Y * (self.outer_size().y * 0.5 - self.thickness)
+    }
     pub fn left_top(&self) -> Vec2 {
         let outer_size = self.outer_size();
         Vec2::new(
@@ -238,6 +274,15 @@ pub struct HoldViewDef {
     pub height: f32,
 }
 #[derive(Debug)]
+pub struct PreviewViewDef {
+    pub h_bg_image: Handle<Image>,
+    pub border_width: f32,
+    pub font_color: Color,
+    pub width: f32,
+    pub height: f32,
+    pub count: usize,
+}
+#[derive(Debug)]
 pub struct ScoreViewDef {
     pub h_bg_image: Handle<Image>,
     pub border_width: f32,
@@ -263,6 +308,7 @@ pub struct PopupDef {
 #[derive(Debug)]
 pub struct UiDef {
     pub hold_view: HoldViewDef,
+    pub preview_view: Option<PreviewViewDef>,
     pub score_view: ScoreViewDef,
     pub manual_view: ManualViewDef,
     pub view_margin_left: f32,
@@ -270,24 +316,32 @@ pub struct UiDef {
     pub popup: PopupDef,
 }
 impl UiDef {
-    pub fn create_with_loading(ron: &UiRon, asset_server: &AssetServer) -> Self {
+    pub fn create_with_loading(ron: &UiRon, loader: &impl HandleLoader) -> Self {
         Self {
             hold_view: HoldViewDef {
-                h_bg_image: asset_server.load(&ron.hold_view.bg_image_asset_path),
+                h_bg_image: loader.load(&ron.hold_view.bg_image_asset_path),
                 border_width: ron.hold_view.border_width,
                 font_color: ron.hold_view.font_color,
                 width: ron.hold_view.width,
                 height: ron.hold_view.height,
             },
+            preview_view: ron.preview_view.as_ref().map(|preview| PreviewViewDef {
+                h_bg_image: loader.load(&preview.bg_image_asset_path),
+                border_width: preview.border_width,
+                font_color: preview.font_color,
+                width: preview.width,
+                height: preview.height,
+                count: preview.count,
+            }),
             score_view: ScoreViewDef {
-                h_bg_image: asset_server.load(&ron.score_view.bg_image_asset_path),
+                h_bg_image: loader.load(&ron.score_view.bg_image_asset_path),
                 border_width: ron.score_view.border_width,
                 font_color: ron.score_view.font_color,
                 width: ron.score_view.width,
                 height: ron.score_view.height,
             },
             manual_view: ManualViewDef {
-                h_bg_image: asset_server.load(&ron.manual_view.bg_image_asset_path),
+                h_bg_image: loader.load(&ron.manual_view.bg_image_asset_path),
                 border_width: ron.manual_view.border_width,
                 font_color: ron.manual_view.font_color,
                 width: ron.manual_view.width,
@@ -296,7 +350,7 @@ impl UiDef {
             view_margin_left: ron.view_margin_left,
             view_margin_y: ron.view_margin_y,
             popup: PopupDef {
-                h_bg_image: asset_server.load(&ron.popup.bg_image_asset_path),
+                h_bg_image: loader.load(&ron.popup.bg_image_asset_path),
                 border_width: ron.popup.border_width,
                 font_color: ron.popup.font_color,
                 font_color_sub: ron.popup.font_color_sub,
@@ -305,12 +359,14 @@ impl UiDef {
     }
 
     fn get_untyped_handles(&self) -> Vec<UntypedHandle> {
-        vec![
+        let mut v = vec![
             self.hold_view.h_bg_image.clone().untyped(),
             self.score_view.h_bg_image.clone().untyped(),
             self.manual_view.h_bg_image.clone().untyped(),
             self.popup.h_bg_image.clone().untyped(),
-        ]
+        ];
+        v.extend(self.preview_view.iter().map(|p| p.h_bg_image.clone().untyped()));
+        v
     }
 }
 
@@ -320,9 +376,9 @@ pub struct BackgroundDef {
     pub offset: Vec2,
 }
 impl BackgroundDef {
-    pub fn create_with_loading(ron: &BackgroundRon, asset_server: &AssetServer) -> Self {
+    pub fn create_with_loading(ron: &BackgroundRon, loader: &impl HandleLoader) -> Self {
         Self {
-            h_bg_image: asset_server.load(&ron.bg_image_asset_path),
+            h_bg_image: loader.load(&ron.bg_image_asset_path),
             offset: ron.offset,
         }
     }
@@ -341,22 +397,32 @@ pub struct SoundDef {
     pub bgm_scale: f32,
     pub h_se_combine: Handle<AudioSource>,
     pub se_combine_scale: f32,
+    pub h_se_warning: Option<Handle<AudioSource>>,
+    pub se_warning_scale: f32,
+    pub bgm_warning_speed: f32,
 }
 impl SoundDef {
-    pub fn create_with_loading(ron: &SoundRon, asset_server: &AssetServer) -> Self {
+    pub fn create_with_loading(ron: &SoundRon, loader: &impl HandleLoader) -> Self {
         Self {
-            h_bgm: asset_server.load(&ron.bgm_asset_path),
+            h_bgm: loader.load(&ron.bgm_asset_path),
             bgm_scale: ron.bgm_scale,
-            h_se_combine: asset_server.load(&ron.se_combine_asset_path),
+            h_se_combine: loader.load(&ron.se_combine_asset_path),
             se_combine_scale: ron.se_combine_scale,
+            h_se_warning: ron.se_warning_asset_path.as_ref().map(|path| loader.load(path)),
+            se_warning_scale: ron.se_warning_scale,
+            bgm_warning_speed: ron.bgm_warning_speed,
         }
     }
 
     fn get_untyped_handles(&self) -> Vec<UntypedHandle> {
-        vec![
+        let mut handles = vec![
             self.h_bgm.clone().untyped(),
             self.h_se_combine.clone().untyped(),
-        ]
+        ];
+        if let Some(h) = &self.h_se_warning {
+            handles.push(h.clone().untyped());
+        }
+        handles
     }
 }
 
@@ -389,6 +455,29 @@ impl RigitBodyDef {
         }
     }
 }
+#[derive(Debug)]
+pub struct ObstacleDef {
+    pub shape: ObstacleShapeRon,
+    /// Relative to the inner bottom center of the bottle.
+    pub position: Vec2,
+    pub rotation: f32,
+    pub angular_velocity: f32,
+    pub h_image: Handle<Image>,
+    pub physics: RigitBodyDef,
+}
+impl ObstacleDef {
+    pub fn create_with_loading(ron: &ObstacleRon, bottle_physics: &RigitBodyRon, loader: &impl HandleLoader) -> Self {
+        Self {
+            shape: ron.shape.clone(),
+            position: ron.position,
+            rotation: ron.rotation,
+            angular_velocity: ron.angular_velocity,
+            h_image: loader.load(&ron.image_asset_path),
+            physics: RigitBodyDef::from_ron(ron.physics.as_ref().unwrap_or(bottle_physics)),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct OtherParamDef {
     pub gravity: f32,
@@ -398,6 +487,9 @@ pub struct OtherParamDef {
     pub max_velocity: f32,
     pub shake_k: f32, // max move is about 0.4 * shake_k
     pub playing_cam_offset: Vec2,
+    pub game_over_grace_sec: f32,
+    pub overflow: OverflowDef,
+    pub time_attack_sec: f32,
 }
 impl OtherParamDef {
     pub fn from_ron(ron: &OtherParamRon) -> Self {
@@ -409,8 +501,161 @@ impl OtherParamDef {
             max_velocity: ron.max_velocity,
             shake_k: ron.shake_k,
             playing_cam_offset: ron.playing_cam_offset,
+            game_over_grace_sec: ron.game_over_grace_sec,
+            overflow: OverflowDef::from_ron(&ron.overflow),
+            time_attack_sec: ron.time_attack_sec,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct OverflowDef {
+    pub ignore_dropping: bool,
+    pub ignore_growing: bool,
+    pub settle_sec: f32,
+}
+impl OverflowDef {
+    pub fn from_ron(ron: &OverflowRon) -> Self {
+        Self {
+            ignore_dropping: ron.ignore_dropping,
+            ignore_growing: ron.ignore_growing,
+            settle_sec: ron.settle_ms as f32 / 1000.,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NextBallDef {
+    /// Weights of levels from `BALL_LEVEL_MIN`.
+    pub weights: Vec<u32>,
+    pub bag: bool,
+    pub max_repeat: Option<u32>,
+}
+impl NextBallDef {
+    pub fn from_ron(ron: &NextBallRon, drop_ball_level_max: BallLevel) -> Self {
+        let weights = if ron.weights.is_empty() {
+            Self::uniform(drop_ball_level_max).weights
+        } else {
+            ron.weights.clone()
+        };
+        Self {
+            weights,
+            bag: ron.bag,
+            max_repeat: ron.max_repeat,
+        }
+    }
+    /// Levels up to `max` are picked with equal probability.
+    pub fn uniform(max: BallLevel) -> Self {
+        Self {
+            weights: vec![1; max.0 - BALL_LEVEL_MIN + 1],
+            bag: false,
+            max_repeat: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HoldDef {
+    pub slots: usize,
+    pub once_per_drop: bool,
+    pub max_holds: Option<u32>,
+}
+impl Default for HoldDef {
+    fn default() -> Self {
+        Self::from_ron(&default())
+    }
+}
+impl HoldDef {
+    pub fn from_ron(ron: &HoldRon) -> Self {
+        Self {
+            slots: ron.slots,
+            once_per_drop: ron.once_per_drop,
+            max_holds: ron.max_holds,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ShakeDef {
+    pub duration_sec: f32,
+    pub tilt: f32,
+    pub cooldown_sec: f32,
+    pub max_shakes: Option<u32>,
+}
+impl ShakeDef {
+    pub fn from_ron(ron: &ShakeRon) -> Self {
+        Self {
+            duration_sec: ron.duration_sec,
+            tilt: ron.tilt,
+            cooldown_sec: ron.cooldown_sec,
+            max_shakes: ron.max_shakes,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ScoringDef {
+    /// Points for combining 2 balls of each level from `BALL_LEVEL_MIN`.
+    pub combine_points: Vec<u32>,
+    pub drop_points: u32,
+    pub max_level_bonus: u32,
+    pub end_bonus_per_ball: u32,
+    pub end_bonus_per_level: u32,
+}
+impl ScoringDef {
+    pub fn from_ron(ron: &ScoringRon, ball_level_max: BallLevel) -> Self {
+        let combine_points = if ron.combine_points.is_empty() {
+            (BALL_LEVEL_MIN..=ball_level_max.0)
+                .map(|lv| (lv * lv) as u32)
+                .collect()
+        } else {
+            ron.combine_points.clone()
+        };
+        Self {
+            combine_points,
+            drop_points: ron.drop_points,
+            max_level_bonus: ron.max_level_bonus,
+            end_bonus_per_ball: ron.end_bonus.per_ball,
+            end_bonus_per_level: ron.end_bonus.per_level,
+        }
+    }
+    /// Points for combining 2 balls of `level`.
+    pub fn get_combine_points(&self, level: BallLevel) -> u32 {
+        self.combine_points.get(level.0 - BALL_LEVEL_MIN).copied().unwrap_or(0)
+    }
+    /// Bonus for balls left of `levels`.
+    pub fn get_end_bonus(&self, levels: impl IntoIterator<Item = BallLevel>) -> u32 {
+        levels.into_iter()
+            .map(|lv| self.end_bonus_per_ball + self.end_bonus_per_level * lv.0 as u32)
+            .sum()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ComboDef {
+    pub chain: ChainRon,
+    pub multipliers: Vec<f32>,
+}
+impl Default for ComboDef {
+    fn default() -> Self {
+        Self::from_ron(&default())
+    }
+}
+impl ComboDef {
+    pub fn from_ron(ron: &ComboRon) -> Self {
+        Self {
+            chain: ron.chain.clone(),
+            multipliers: ron.multipliers.clone(),
         }
     }
+    /// Multiplier of the `chain`th combine in a chain (1-origin).
+    pub fn multiplier(&self, chain: u32) -> f32 {
+        let idx = (chain.max(1) - 1) as usize;
+        self.multipliers.get(idx)
+            .or(self.multipliers.last())
+            .copied()
+            .unwrap_or(1.)
+    }
 }
 
 
@@ -419,8 +664,14 @@ pub struct GameAssets {
     ball_level_settings: Vec<BallLevelDef>,
     effects: Vec<EffectDef>,
     pub drop_ball_level_max: BallLevel,
+    pub next_ball: NextBallDef,
+    pub hold: HoldDef,
+    pub shake: ShakeDef,
+    pub scoring: ScoringDef,
+    pub combo: ComboDef,
     pub player_settings: PlayerDef,
     pub bottle_settings: BottleDef,
+    pub obstacles: Vec<ObstacleDef>,
     pub h_font: Handle<Font>,
 
     pub background: BackgroundDef,
@@ -432,6 +683,8 @@ pub struct GameAssets {
     pub ball_physics: RigitBodyDef,
     pub bottle_physics: RigitBodyDef,
     pub physics: OtherParamDef,
+
+    pub missions: Vec<MissionRon>,
 }
 impl Loadable for GameAssets {
     fn get_untyped_handles(&self) -> Vec<UntypedHandle> {
@@ -449,11 +702,15 @@ impl Loadable for GameAssets {
         let mut v6 = self.effects.iter()
             .flat_map(|x| x.get_untyped_handles())
             .collect();
+        let mut v7 = self.obstacles.iter()
+            .map(|x| x.h_image.clone().untyped())
+            .collect();
         v.append(&mut v2);
         v.append(&mut v3);
         v.append(&mut v4);
         v.append(&mut v5);
         v.append(&mut v6);
+        v.append(&mut v7);
         v
     }
 }
@@ -472,11 +729,6 @@ impl BallLevel {
         assert!(lv >= BALL_LEVEL_MIN);
         Self(lv)
     }
-    pub fn from_rand_u32(rnd: u32, min: BallLevel, max: BallLevel) -> Self {
-        Self::new(
-            (rnd as usize % (max.0-min.0+1)) + min.0
-        )
-    }
 }
 
 impl GameAssets {
@@ -485,8 +737,14 @@ impl GameAssets {
         ball_level_settings: Vec<BallLevelDef>,
         effects: Vec<EffectDef>,
         drop_ball_level_max: BallLevel,
+        next_ball: NextBallDef,
+        hold: HoldDef,
+        shake: ShakeDef,
+        scoring: ScoringDef,
+        combo: ComboDef,
         player_settings: PlayerDef,
         bottle_settings: BottleDef,
+        obstacles: Vec<ObstacleDef>,
         background: BackgroundDef,
         ui: UiDef,
         h_font: Handle<Font>,
@@ -496,13 +754,20 @@ impl GameAssets {
         bottle_physics: RigitBodyDef,
         physics: OtherParamDef,
 
+        missions: Vec<MissionRon>,
     ) -> Self {
         Self {
             ball_level_settings,
             effects,
             drop_ball_level_max,
+            next_ball,
+            hold,
+            shake,
+            scoring,
+            combo,
             player_settings,
             bottle_settings,
+            obstacles,
             h_font,
             background,
             ui,
@@ -510,8 +775,53 @@ impl GameAssets {
             ball_physics,
             bottle_physics,
             physics,
+            missions,
         }
     }
+    pub fn create_with_loading(from_ron: &GameRon, loader: &impl HandleLoader) -> Self {
+        let balls = from_ron.balls.iter()
+            .map(|n| BallLevelDef::create_with_loading(n, loader))
+            .collect();
+        let effects = from_ron.effects.iter()
+            .map(|r| EffectDef::create_with_loading(r, loader))
+            .collect();
+        let player = PlayerDef::create_with_loading(&from_ron.player, loader);
+        let bottle = BottleDef::create_with_loading(&from_ron.bottle, loader);
+        let obstacles = from_ron.obstacles.iter()
+            .map(|r| ObstacleDef::create_with_loading(r, &from_ron.bottle_physics, loader))
+            .collect();
+        let background = BackgroundDef::create_with_loading(&from_ron.background, loader);
+        let ui = UiDef::create_with_loading(&from_ron.ui, loader);
+        let sound = SoundDef::create_with_loading(&from_ron.sounds, loader);
+        let ball_physics = RigitBodyDef::from_ron(&from_ron.ball_physics);
+        let bottle_physics = RigitBodyDef::from_ron(&from_ron.bottle_physics);
+        let physics = OtherParamDef::from_ron(&from_ron.physics);
+        let drop_ball_level_max = BallLevel(from_ron.drop_ball_level_max);
+        let next_ball = NextBallDef::from_ron(&from_ron.next_ball, drop_ball_level_max);
+
+        Self::new(
+            balls,
+            effects,
+            drop_ball_level_max,
+            next_ball,
+            HoldDef::from_ron(&from_ron.hold),
+            ShakeDef::from_ron(&from_ron.shake),
+            ScoringDef::from_ron(&from_ron.scoring, BallLevel(from_ron.balls.len())),
+            ComboDef::from_ron(&from_ron.combo),
+            player,
+            bottle,
+            obstacles,
+            background,
+            ui,
+            loader.load("embedded://suika_clone/embedded_assets/fonts/x12y12pxMaruMinyaM.ttf"),
+            sound,
+            ball_physics,
+            bottle_physics,
+            physics,
+            from_ron.missions.clone(),
+        )
+    }
+
     pub fn get_ball_image(&self, level: BallLevel) -> &Handle<Image> {
         &self.get_ball_setting(level).h_image
     }
@@ -535,15 +845,27 @@ impl GameAssets {
     }
 
     pub fn get_ball_effect(&self, lv_combined: BallLevel) -> Option<&EffectDef> {
-        if let Some(idx) = self.get_ball_setting(lv_combined).effect_index {
-            Some(
-                &self.effects[idx]
-            )
-        } else {
-            None
+        self.get_ball_setting(lv_combined).effect_index
+            .and_then(|idx| self.effects.get(idx))
+    }
+
+    /// Density of the ball. `mass` is converted to it by the area of the shape.
+    pub fn get_ball_density(&self, lv: BallLevel) -> f32 {
+        let s = self.get_ball_setting(lv);
+        match (s.physics.density, s.physics.mass) {
+            (Some(density), _) => density,
+            (None, Some(mass)) => mass / (s.shape.area() * s.physics_radius * s.physics_radius),
+            (None, None) => 1.,
         }
     }
 
+    /// Radius of the largest circle in the shape of the ball.
+    #[inline]
+    pub fn get_ball_inner_r(&self, lv: BallLevel) -> f32 {
+        let s = self.get_ball_setting(lv);
+        s.shape.inner_radius() * s.physics_radius
+    }
+
     #[inline]
     pub fn get_ball_start_r(&self, lv: BallLevel) -> f32 {
         //
@@ -563,10 +885,11 @@ impl GameAssets {
         //   |  ***
         //   _  *
         //
-        let r = self.get_ball_r(BallLevel::new(BALL_LEVEL_MIN));
-        let y = self.get_ball_r(BallLevel::new(lv.0 - 1));
+        // Radii of circles in shapes for non-circular balls.
+        let r = self.get_ball_inner_r(BallLevel::new(BALL_LEVEL_MIN));
+        let y = self.get_ball_inner_r(BallLevel::new(lv.0 - 1));
 
-        (2. * r * y + r * r).powf(1. / 2.) - r
+        ((2. * r * y + r * r).powf(1. / 2.) - r).min(self.get_ball_r(lv))
     }
 
     #[inline]
@@ -606,6 +929,23 @@ impl GameAssets {
         )
     }
 
+    pub fn countdown_view_size(&self) -> Vec2 {
+        Vec2::new(
+            self.ui.score_view.width,
+            self.ui.score_view.height * 0.5,
+        )
+    }
+
+    /// Above the score view.
+    pub fn countdown_view_center(&self) -> Vec2 {
+        Vec2::new(
+            self.score_center().x,
+            self.bottle_settings.left_top().y
+                + self.ui.view_margin_y
+                + self.countdown_view_size().y * 0.5,
+        )
+    }
+
     pub fn hold_view_size(&self) -> Vec2 {
         Vec2::new(
             self.ui.hold_view.width,
@@ -624,6 +964,58 @@ impl GameAssets {
         )
     }
 
+    pub fn mission_view_size(&self) -> Vec2 {
+        Vec2::new(
+            self.ui.score_view.width,
+            self.ui.hold_view.height,
+        )
+    }
+
+    /// Right of the hold view.
+    pub fn mission_view_center(&self) -> Vec2 {
+        let hold_center = self.hold_view_center();
+        Vec2::new(
+            hold_center.x
+                + self.ui.hold_view.width * 0.5
+                + self.ui.view_margin_left
+                + self.mission_view_size().x * 0.5,
+            hold_center.y,
+        )
+    }
+
+    /// Number of upcoming balls shown. 0 if there is no preview view.
+    pub fn preview_len(&self) -> usize {
+        self.ui.preview_view.as_ref().map(|p| p.count).unwrap_or(0)
+    }
+
+    pub fn preview_view_size(&self) -> Vec2 {
+        self.ui.preview_view.as_ref()
+            .map(|p| Vec2::new(p.width, p.height))
+            .unwrap_or(Vec2::ZERO)
+    }
+
+    /// Under the hold view.
+    pub fn preview_view_center(&self) -> Vec2 {
+        Vec2::new(
+            self.bottle_settings.right_bottom().x
+                + self.ui.view_margin_left
+                + self.preview_view_size().x * 0.5,
+            self.hold_view_center().y
+                - self.ui.hold_view.height * 0.5
+                - self.ui.view_margin_y
+                - self.preview_view_size().y * 0.5,
+        )
+    }
+
+    /// Height of the preview view and its margin. 0 if there is no preview view.
+    fn preview_view_space(&self) -> f32 {
+        if self.ui.preview_view.is_some() {
+            self.preview_view_size().y + self.ui.view_margin_y
+        } else {
+            0.
+        }
+    }
+
     pub fn manual_view_size(&self) -> Vec2 {
         Vec2::new(
             self.ui.manual_view.width,
@@ -641,6 +1033,7 @@ impl GameAssets {
                 - self.ui.view_margin_y
                 - self.ui.hold_view.height
                 - self.ui.view_margin_y
+                - self.preview_view_space()
                 - self.ui.manual_view.height * 0.5,
         )
     }
@@ -1,6 +1,6 @@
 use crate::prelude::*;
 use bevy::prelude::*;
-use game_ron::GameRon;
+use game_ron::{GameRon, PuzzleRon};
 use crate::embedded_assets::assets::DEFAULT_GAME_RON_PATH;
 
 #[derive(Resource, Debug, Clone)]
@@ -13,7 +13,23 @@ impl Loadable for CurrentGameRon {
     }
 }
 
+/// Loaded only in the puzzle mode.
+#[derive(Resource, Debug, Clone, Default)]
+#[derive(Reflect)]
+pub struct CurrentPuzzleRon(pub Option<Handle<PuzzleRon>>);
+
+impl Loadable for CurrentPuzzleRon {
+    fn get_untyped_handles(&self) -> Vec<UntypedHandle> {
+        self.0.iter().map(|h| h.clone().untyped()).collect()
+    }
+}
+
 const DEFAULT_GAME_RON_NAME: &str = "(default)";
 pub fn get_default_game_ron_name_and_asset_path() -> (&'static str, &'static str) {
     (DEFAULT_GAME_RON_NAME, DEFAULT_GAME_RON_PATH)
 }
+
+/// The first puzzle of `ron/puzzles.list.ron`.
+pub fn get_default_puzzle_name_and_asset_path() -> (&'static str, &'static str) {
+    ("Puzzle 1", "ron/puzzles/01.puzzle.ron")
+}
@@ -1,13 +1,15 @@
+use std::collections::HashSet;
 use std::f32::consts::PI;
 use crate::prelude::*;
 use bevy::{
     prelude::*, sprite::{Material2d, MaterialMesh2dBundle, Mesh2dHandle},
+    render::{mesh::{Indices, PrimitiveTopology}, render_asset::RenderAssetUsages},
 };
 use bevy_xpbd_2d::prelude::*;
 use itertools::Itertools;
+use serde::{Deserialize, Serialize};
 
 use bevy_rand::prelude::*;
-use bevy_rand::resource::GlobalEntropy;
 use bevy_prng::ChaCha8Rng;
 
 mod common;
@@ -17,21 +19,48 @@ mod game_over_popup;
 use game_over_popup::*;
 mod pause_popup;
 use pause_popup::*;
-use rand_core::RngCore;
+use rand_core::{RngCore, SeedableRng};
 
 mod effects;
-
-
+mod time_attack;
+mod flood;
+mod combo;
+mod next_ball;
+mod preview;
+mod mission;
+mod puzzle;
+mod warning;
+mod overflow;
+mod obstacles;
+pub use puzzle::Puzzle;
+mod replay;
+use replay::*;
+pub use replay::{Replay, ReplayMode};
+#[cfg(test)]
+mod headless;
+#[cfg(test)]
+mod tests;
+
+
+/// The game screen: views, sounds, popups and inputs on top of `ScGameSimPlugin`.
 pub struct ScGameScreenPlugin;
 
+/// The game simulation only (player, balls, bottle, scoring and game over).
+/// It requires neither a window nor a renderer.
+pub struct ScGameSimPlugin;
+
 // Physics Engine Settings
+const FIXED_TIMESTEP_HZ: f64 = 60.;
 const GRAVITY_SCALE: f32 = 9.81 * 100.;
 const XPBD_SUBSTEP: u32 = 32;
 
-impl Plugin for ScGameScreenPlugin {
+impl Plugin for ScGameSimPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(Gravity(Vec2::NEG_Y * GRAVITY_SCALE));
         app.insert_resource(SubstepCount(XPBD_SUBSTEP));
+        // One physics step per fixed update so that a replay reproduces the same simulation.
+        app.insert_resource(Time::<Fixed>::from_hz(FIXED_TIMESTEP_HZ));
+        app.insert_resource(Time::new_with(Physics::fixed_once_hz(FIXED_TIMESTEP_HZ)));
 
 
         app.insert_state(GameScreenState::Inactive);
@@ -39,6 +68,152 @@ impl Plugin for ScGameScreenPlugin {
         app.add_event::<BallEvent>();
         app.add_event::<PlayerInputEvent>();
         app.add_event::<BallSpawnEvent>();
+        app.add_event::<combo::ComboEvent>();
+
+        app.init_resource::<ReplayMode>();
+        app.init_resource::<ReplayRecorder>();
+        app.init_resource::<LastReplay>();
+        app.init_resource::<PlayFrame>();
+        app.init_resource::<PlaySeed>();
+        app.init_resource::<PlayGameMode>();
+        app.init_resource::<PlayCond>();
+        app.init_resource::<PlayerInputQueue>();
+        app.init_resource::<mission::MissionProgress>();
+        app.init_resource::<mission::ActiveMissions>();
+        app.init_resource::<puzzle::OutOfBallsSince>();
+        app.init_resource::<combo::Combo>();
+        app.init_resource::<warning::Warning>();
+
+        // GameScreenState :: Init
+        app.add_systems(OnEnter(GameScreenState::Init), (
+            setup_physics_param,
+            setup_replay,
+            spawn_bottle,
+            obstacles::spawn_obstacles
+                .after(spawn_bottle)
+                .run_if(obstacles::has_obstacles),
+            spawn_player
+                .after(setup_replay),
+            mission::setup_missions
+                .after(setup_replay),
+            puzzle::reset_out_of_balls,
+            combo::reset_combo,
+            warning::reset_warning,
+
+            start_playing,
+        ));
+
+        // GameScreenState :: Playing
+        app.add_systems(FixedUpdate, (
+            dispatch_player_inputs,
+            grow_ball_spawned,
+            check_ball_collisions,
+            check_dropping_ball,
+            move_puppeteer
+                .after(dispatch_player_inputs),
+            puppet_player_pos.after(move_puppeteer),
+            sync_puppetter_shape_caster
+                .after(puppet_player_pos),
+            pause_game
+                .after(dispatch_player_inputs),
+            action_player
+                .after(dispatch_player_inputs)
+                .after(check_dropping_ball),
+            shake_bottle
+                .after(dispatch_player_inputs),
+            combine_balls_touched
+                .after(check_ball_collisions),
+            spawn_ball
+                .after(action_player)
+                .after(combine_balls_touched),
+            score_ball_events
+                .run_if(not(in_game_mode(GameMode::Flood))),
+            overflow::track_resting_balls
+                .before(check_game_over)
+                .run_if(overflow::needs_settle),
+            check_game_over,
+            air_damping_balls,
+            obstacles::move_rotating_obstacles
+                .after(shake_bottle)
+                .run_if(obstacles::has_obstacles),
+        ).run_if(in_state(GameScreenState::Playing))
+            .before(PhysicsSet::Prepare));
+
+        // Game modes
+        app.add_systems(FixedUpdate, (
+            flood::score_drops
+                .after(action_player)
+                .run_if(in_game_mode(GameMode::Flood)),
+            time_attack::check_time_over
+                .after(dispatch_player_inputs)
+                .run_if(in_game_mode(GameMode::TimeAttack)),
+            mission::track_mission_progress
+                .after(combine_balls_touched)
+                .run_if(mission::has_missions),
+            // A game over in the same frame wins.
+            mission::check_missions_cleared
+                .after(mission::track_mission_progress)
+                .after(score_ball_events)
+                .before(check_game_over)
+                .run_if(mission::has_missions),
+            // A clear in the same frame wins.
+            puzzle::check_out_of_balls
+                .after(action_player)
+                .after(check_dropping_ball)
+                .before(mission::check_missions_cleared)
+                .run_if(in_game_mode(GameMode::Puzzle)),
+            warning::check_warning
+                .after(check_dropping_ball)
+                .run_if(warning::has_warning_line),
+        ).run_if(in_state(GameScreenState::Playing))
+            .before(PhysicsSet::Prepare));
+
+        // GameScreenState :: GameOver
+        app.add_systems(OnEnter(GameScreenState::GameOver), (
+            physics_pause,
+            finish_recording
+                .run_if(is_recording),
+            add_end_bonus
+                .run_if(not(in_game_mode(GameMode::Flood))),
+        ));
+        app.add_systems(OnExit(GameScreenState::GameOver), (
+            physics_restart,
+            cleanup_ingame_entites,
+        ));
+
+        // GameScreenState :: Cleared
+        app.add_systems(OnEnter(GameScreenState::Cleared), (
+            physics_pause,
+            finish_recording
+                .run_if(is_recording),
+            add_end_bonus
+                .run_if(not(in_game_mode(GameMode::Flood))),
+        ));
+        app.add_systems(OnExit(GameScreenState::Cleared), (
+            physics_restart,
+            cleanup_ingame_entites,
+        ));
+
+        // GameScreenState :: Paused
+        app.add_systems(OnEnter(GameScreenState::Paused), (
+            physics_pause,
+        ));
+        app.add_systems(OnExit(GameScreenState::Paused), (
+            physics_restart,
+        ));
+
+        // GameScreenState :: Restart
+        app.add_systems(OnEnter(GameScreenState::Restart), (
+            physics_restart,
+            cleanup_ingame_entites,
+            |mut next: ResMut<NextState<GameScreenState>>| { next.set(GameScreenState::Init); }, // FIXME: Re-design states
+        ));
+    }
+}
+
+impl Plugin for ScGameScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(ScGameSimPlugin);
 
         // GameState :: InGame
         app.add_systems(OnEnter(GameState::InGame), (
@@ -53,6 +228,7 @@ impl Plugin for ScGameScreenPlugin {
             cleanup_gameover_popup,
             stop_bgm,
             inactivate_game_screen,
+            reset_replay_mode,
         ));
         app.add_systems(Update, (
             camera::update_camera,
@@ -61,72 +237,76 @@ impl Plugin for ScGameScreenPlugin {
 
         // GameScreenState :: Init
         app.add_systems(OnEnter(GameScreenState::Init), (
-            setup_physics_param,
             effects::spawn_effect_manager, // FIXME: can i initialize in building app ?
             spawn_background,
-            spawn_bottle,
-            spawn_player,
-            spawn_score_view,
+            spawn_bottle_view
+                .after(spawn_bottle),
+            warning::spawn_warning_line
+                .after(spawn_bottle)
+                .run_if(warning::has_warning_line),
+            obstacles::spawn_obstacle_views
+                .after(obstacles::spawn_obstacles)
+                .run_if(obstacles::has_obstacles),
+            spawn_player_view
+                .after(spawn_player),
+            spawn_score_view
+                .after(setup_replay),
             spwan_holding_ball_view,
+            preview::spawn_preview_view
+                .run_if(preview::has_preview),
             spawn_manual_view,
+            time_attack::spawn_countdown_view
+                .after(setup_replay)
+                .run_if(in_game_mode(GameMode::TimeAttack)),
+            mission::spawn_mission_view
+                .after(mission::setup_missions)
+                .run_if(mission::has_missions),
             start_play_bgm,
-
-            start_playing,
         ));
 
         // GameScreenState :: Playing
         app.add_systems(Update, (
-            read_keyboard_for_player_actions,
-            read_gamepad_for_player_actions,
-            grow_ball_spawned,
-            check_ball_collisions,
-            check_dropping_ball,
-            move_puppeteer
-                .after(read_gamepad_for_player_actions)
-                .after(read_keyboard_for_player_actions),
-            puppet_player_pos.after(move_puppeteer),
-            sync_guide.after(puppet_player_pos),
-            sync_puppetter_shape_caster
-                .after(sync_guide),
-            pause_game
-                .after(read_gamepad_for_player_actions)
-                .after(read_keyboard_for_player_actions),
-            action_player
-                .after(read_gamepad_for_player_actions)
-                .after(read_keyboard_for_player_actions)
-                .after(check_dropping_ball),
-            shake_bottle
-                .after(read_gamepad_for_player_actions)
-                .after(read_keyboard_for_player_actions),
-            combine_balls_touched
-                .after(check_ball_collisions),
-            spawn_ball
-                .after(action_player)
-                .after(combine_balls_touched),
-            play_se_combine_balls
-                .after(action_player)
-                .after(combine_balls_touched),
-            update_player_view
-                .after(action_player),
-            score_ball_events,
-            check_game_over,
-            spwan_effects_balls_touched
-                .after(check_ball_collisions),
+            reset_held_move,
+            read_keyboard_for_player_actions
+                .after(reset_held_move),
+            read_gamepad_for_player_actions
+                .after(reset_held_move),
+            spawn_ball_view,
+            sync_guide,
+            play_se_combine_balls,
+            update_player_view,
+            preview::update_preview_view
+                .run_if(preview::has_preview),
             effects::update_effect,
+            combo::spawn_combo_popups,
+            time_attack::update_countdown_view
+                .run_if(in_game_mode(GameMode::TimeAttack)),
+            mission::update_mission_view
+                .run_if(mission::has_missions),
+            warning::update_warning_view
+                .run_if(warning::has_warning_line),
         ).run_if(in_state(GameScreenState::Playing)));
-        app.add_systems(Update, (
-            air_damping_balls,
+        app.add_systems(OnExit(GameScreenState::Playing), (
+            warning::reset_bgm_speed,
+        ));
+        app.add_systems(FixedUpdate, (
+            // Balls combined are despawned in this frame.
+            spwan_effects_balls_touched
+                .after(check_ball_collisions)
+                .before(combine_balls_touched),
         ).run_if(in_state(GameScreenState::Playing)));
 
         // GameScreenState :: GameOver
         app.add_event::<GameOverPopupInput>();
         app.add_systems(OnEnter(GameScreenState::GameOver), (
-            physics_pause,
-            setup_gameover_popup,
+            setup_gameover_popup
+                .after(add_end_bonus),
             record_score
-                .after(setup_gameover_popup),
+                .after(setup_gameover_popup)
+                .run_if(is_recording),
             save_scores
-                .after(record_score),
+                .after(record_score)
+                .run_if(is_recording),
             move_camera_to_ball_protruded,
         ));
 
@@ -137,19 +317,32 @@ impl Plugin for ScGameScreenPlugin {
             act_gameover_popup
                 .after(read_keyboard_for_gameover_popup)
                 .after(read_gamepad_for_gameover_popup),
-        ).run_if(in_state(GameScreenState::GameOver)));
+        ).run_if(in_state(GameScreenState::GameOver)
+            .or_else(in_state(GameScreenState::Cleared))));
 
         app.add_systems(OnExit(GameScreenState::GameOver), (
-            physics_restart,
             cleanup_gameover_popup,
-            cleanup_ingame_entites,
             move_camera_to_default,
         ));
 
+        // GameScreenState :: Cleared (shares the game over popup)
+        app.add_systems(OnEnter(GameScreenState::Cleared), (
+            setup_gameover_popup
+                .after(add_end_bonus),
+            record_score
+                .after(setup_gameover_popup)
+                .run_if(is_recording),
+            save_scores
+                .after(record_score)
+                .run_if(is_recording),
+        ));
+        app.add_systems(OnExit(GameScreenState::Cleared), (
+            cleanup_gameover_popup,
+        ));
+
         // GameScreenState :: Paused
         app.add_event::<PausePopupInput>();
         app.add_systems(OnEnter(GameScreenState::Paused), (
-            physics_pause,
             setup_pause_popup,
         ));
 
@@ -163,16 +356,8 @@ impl Plugin for ScGameScreenPlugin {
         ).run_if(in_state(GameScreenState::Paused)));
 
         app.add_systems(OnExit(GameScreenState::Paused), (
-            physics_restart,
             cleanup_pause_popup,
         ));
-
-        // GameScreenState :: Restart
-        app.add_systems(OnEnter(GameScreenState::Restart), (
-            physics_restart,
-            cleanup_ingame_entites,
-            |mut next: ResMut<NextState<GameScreenState>>| { next.set(GameScreenState::Init); }, // FIXME: Re-design states
-        ));
     }
 }
 
@@ -197,12 +382,18 @@ fn setup_physics_param(
 
 fn record_score(
     q_player: Query<&Player>,
-    config: Res<Config>,
+    play_cond: Res<PlayCond>,
+    play_seed: Res<PlaySeed>,
+    combo: Res<combo::Combo>,
+    state: Res<State<GameScreenState>>,
     mut scores: ResMut<Scores>,
 ) {
+    // A score of a puzzle means that it is cleared.
+    if play_cond.0.game_mode == GameMode::Puzzle && *state.get() != GameScreenState::Cleared {
+        return;
+    }
     if let Ok(player) = q_player.get_single() {
-        let game_cnd = GameCond::new(&config.game_ron_name);
-        scores.push(&game_cnd, Score::new(player.score));
+        scores.push(&play_cond.0, Score::new(player.score, play_seed.0).with_combo(combo.stats));
     }
 }
 
@@ -279,63 +470,55 @@ fn physics_pause(
     physics_time.pause();
 }
 
-/// Spawn bottle at the origin (x:0,y:0)
+/// Spawn bottle at the origin (x:0,y:0) and balls of the puzzle in it.
 fn spawn_bottle(
     mut commands: Commands,
+    puzzle: Option<Res<Puzzle>>,
     assets: Res<GameAssets>,
 ) {
     let bottle_center = assets.bottle_center();
     let bottle_outer_size = assets.bottle_outer_size();
-    let image_border = assets.bottle_settings.image_border;
     // Spawn Bottle
     commands.spawn((
         Bottle {
             origin: bottle_center,
         },
+        Shaking::default(),
         RigidBody::Kinematic,
+        LinearVelocity::ZERO,
+        AngularVelocity::ZERO,
         SpatialBundle {
             transform: Transform::from_translation(bottle_center.extend(Z_WALL)),
             ..default()
         },
     ))
     .with_children(|b| {
-        // fg
-        b.spawn((
-            SpriteBundle {
-                texture: assets.bottle_settings.h_fg_image.clone(),
-                sprite: Sprite {
-                    custom_size: Some(bottle_outer_size),
-                    ..default()
-                },
-                transform: Transform::from_translation(Vec2::ZERO.extend(0.02)),
+        let physics_param = (
+            Restitution {
+                coefficient: assets.bottle_physics.restitution.coef,
                 ..default()
             },
-            ImageScaleMode::Sliced(TextureSlicer {
-                border: BorderRect::square(image_border),
-                center_scale_mode: SliceScaleMode::Stretch,
-                sides_scale_mode: SliceScaleMode::Stretch,
-                ..default()
-            }),
-        ));
-
-        // bg
-        b.spawn((
-            SpriteBundle {
-                texture: assets.bottle_settings.h_bg_image.clone(),
-                sprite: Sprite {
-                    custom_size: Some(bottle_outer_size),
-                    ..default()
-                },
-                transform: Transform::from_translation(Vec2::ZERO.extend(Z_BACK-Z_WALL+0.01)),
+            Friction {
+                dynamic_coefficient: assets.bottle_physics.friction.dynamic_coef,
+                static_coefficient: assets.bottle_physics.friction.static_coef,
                 ..default()
             },
-            ImageScaleMode::Sliced(TextureSlicer {
-                border: BorderRect::square(image_border),
-                center_scale_mode: SliceScaleMode::Stretch,
-                sides_scale_mode: SliceScaleMode::Stretch,
-                ..default()
-            }),
-        ));
+        );
+
+        if let Some(outline) = assets.bottle_settings.local_outline() {
+            // A wall for each edge, outside of the outline
+            let r = assets.bottle_settings.thickness / 2.;
+            for (&v0, &v1) in outline.iter().tuple_windows() {
+                let outward = -(v1 - v0).perp().normalize_or_zero() * r;
+                b.spawn((
+                    BottleWall,
+                    Collider::capsule_endpoints(v0 + outward, v1 + outward, r),
+                    physics_param,
+                    TransformBundle::default(),
+                ));
+            }
+            return;
+        }
 
         let bottom_c = Vec2::Y * -(0.5 * bottle_outer_size.y - assets.bottle_settings.thickness/2.);
         let left_bottle_c = Vec2::X * -(0.5 * bottle_outer_size.x - assets.bottle_settings.thickness/2.);
@@ -348,18 +531,6 @@ fn spawn_bottle(
         let len_side = side_size.y - side_size.x;
         let r_side = side_size.x/2.;
 
-        let physics_param = (
-            Restitution {
-                coefficient: assets.bottle_physics.restitution.coef,
-                ..default()
-            },
-            Friction {
-                dynamic_coefficient: assets.bottle_physics.friction.dynamic_coef,
-                static_coefficient: assets.bottle_physics.friction.static_coef,
-                ..default()
-            },
-        );
-
         // Bottom
         b.spawn((
             BottleWall,
@@ -394,6 +565,133 @@ fn spawn_bottle(
             },
         ));
     });
+
+    if let Some(puzzle) = puzzle {
+        let bottom_c = assets.bottle_settings.inner_bottom_center();
+        for ball in puzzle.0.balls.iter() {
+            let level = BallLevel::new(ball.level);
+            commands.spawn((
+                Ball::new(level),
+                RigidBody::Dynamic,
+                create_ball_collider(level, assets.get_ball_r(level), &assets),
+                create_ball_physics_param(level, &assets),
+                create_ball_spatial(level, bottom_c + ball.position),
+            ));
+        }
+    }
+}
+
+/// A mesh filling `outline` with a texture fitted to its bounding box.
+/// Triangles are fanned out from the center of vertices.
+fn create_outline_mesh(outline: &[Vec2]) -> Mesh {
+    let center = outline.iter().copied().sum::<Vec2>() / outline.len() as f32;
+    let min = outline.iter().copied().reduce(Vec2::min).unwrap_or_default();
+    let max = outline.iter().copied().reduce(Vec2::max).unwrap_or_default();
+    let size = (max - min).max(Vec2::splat(f32::EPSILON));
+    let vertices = std::iter::once(center).chain(outline.iter().copied()).collect_vec();
+
+    let positions = vertices.iter().map(|v| [v.x, v.y, 0.]).collect_vec();
+    let normals = vec![[0., 0., 1.]; vertices.len()];
+    let uvs = vertices.iter()
+        .map(|v| [(v.x - min.x) / size.x, (max.y - v.y) / size.y])
+        .collect_vec();
+    // The opening is closed between the last vertex and the first one.
+    let n = outline.len() as u32;
+    let indices = (0..n)
+        .flat_map(|i| [0, i + 1, (i + 1) % n + 1])
+        .collect_vec();
+    Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+        .with_inserted_indices(Indices::U32(indices))
+}
+
+fn spawn_bottle_view(
+    mut commands: Commands,
+    q_bottle: Query<Entity, With<Bottle>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    assets: Res<GameAssets>,
+) {
+    let bottle_outer_size = assets.bottle_outer_size();
+    let image_border = assets.bottle_settings.image_border;
+    if let (Ok(bottle), Some(outline)) = (q_bottle.get_single(), assets.bottle_settings.local_outline()) {
+        let thickness = assets.bottle_settings.thickness;
+        commands.entity(bottle).with_children(|b| {
+            // fg: along each wall
+            for (&v0, &v1) in outline.iter().tuple_windows() {
+                let d = v1 - v0;
+                let outward = -d.perp().normalize_or_zero() * thickness / 2.;
+                b.spawn((
+                    SpriteBundle {
+                        texture: assets.bottle_settings.h_fg_image.clone(),
+                        sprite: Sprite {
+                            custom_size: Some(Vec2::new(d.length() + thickness, thickness)),
+                            ..default()
+                        },
+                        transform: Transform::from_translation(((v0 + v1) / 2. + outward).extend(0.02))
+                            .with_rotation(Quat::from_rotation_z(d.y.atan2(d.x))),
+                        ..default()
+                    },
+                    ImageScaleMode::Sliced(TextureSlicer {
+                        border: BorderRect::square(image_border),
+                        center_scale_mode: SliceScaleMode::Stretch,
+                        sides_scale_mode: SliceScaleMode::Stretch,
+                        ..default()
+                    }),
+                ));
+            }
+
+            // bg: inside of the outline
+            b.spawn(MaterialMesh2dBundle {
+                mesh: meshes.add(create_outline_mesh(&outline)).into(),
+                material: materials.add(assets.bottle_settings.h_bg_image.clone()),
+                transform: Transform::from_translation(Vec2::ZERO.extend(Z_BACK-Z_WALL+0.01)),
+                ..default()
+            });
+        });
+    } else if let Ok(bottle) = q_bottle.get_single() {
+        commands.entity(bottle).with_children(|b| {
+            // fg
+            b.spawn((
+                SpriteBundle {
+                    texture: assets.bottle_settings.h_fg_image.clone(),
+                    sprite: Sprite {
+                        custom_size: Some(bottle_outer_size),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(Vec2::ZERO.extend(0.02)),
+                    ..default()
+                },
+                ImageScaleMode::Sliced(TextureSlicer {
+                    border: BorderRect::square(image_border),
+                    center_scale_mode: SliceScaleMode::Stretch,
+                    sides_scale_mode: SliceScaleMode::Stretch,
+                    ..default()
+                }),
+            ));
+
+            // bg
+            b.spawn((
+                SpriteBundle {
+                    texture: assets.bottle_settings.h_bg_image.clone(),
+                    sprite: Sprite {
+                        custom_size: Some(bottle_outer_size),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(Vec2::ZERO.extend(Z_BACK-Z_WALL+0.01)),
+                    ..default()
+                },
+                ImageScaleMode::Sliced(TextureSlicer {
+                    border: BorderRect::square(image_border),
+                    center_scale_mode: SliceScaleMode::Stretch,
+                    sides_scale_mode: SliceScaleMode::Stretch,
+                    ..default()
+                }),
+            ));
+        });
+    }
 }
 
 /// Spawn background image at the center of the default camera position with offset.
@@ -448,20 +746,25 @@ fn check_ball_collisions(
         }
     }
 
+    for touch in coalesce_touches(touches) {
+        ev_ball.send(BallEvent::TouchSameLevel(touch.0, touch.1));
+    }
+}
+
+/// Keeps only one touch for a ball touching 2 or more balls in the same frame,
+/// so that no ball is combined twice.
+fn coalesce_touches(touches: Vec<(Entity, Entity)>) -> impl Iterator<Item = (Entity, Entity)> {
     // check whether 3 balls are colliding in same frame.
-    let touches = touches.into_iter()
+    let mut used = HashSet::new();
+    touches.into_iter()
         .sorted_by(|l, r| Ord::cmp(&l.0, &r.0))
-        .coalesce(|l, r|
-            if l.0 == r.0 {
-                Ok(l)
-            } else {
-                Err((l, r))
+        .filter(move |&(e1, e2)| {
+            if used.contains(&e1) || used.contains(&e2) {
+                return false;
             }
-        );
-
-    for touch in touches {
-        ev_ball.send(BallEvent::TouchSameLevel(touch.0, touch.1));
-    }
+            used.extend([e1, e2]);
+            true
+        })
 }
 
 fn combine_balls_touched(
@@ -532,52 +835,104 @@ fn spwan_effects_balls_touched( // FIXME: common logic! see: combine_balls_touch
 fn score_ball_events(
     mut q_player: Query<&mut Player>,
     mut ev_ball: EventReader<BallSpawnEvent>,
+    mut ev_combo: EventWriter<combo::ComboEvent>,
+    mut combo: ResMut<combo::Combo>,
+    play_frame: Res<PlayFrame>,
 
     sc_asset: Res<GameAssets>,
 ) {
     if let Ok(mut player) = q_player.get_single_mut() {
-        let score: u32 = ev_ball.read()
-            .map(|ev| match ev {
+        for ev in ev_ball.read() {
+            match ev {
                 BallSpawnEvent::Drop(_, _level) => {
-                    //level.0 as u32 * 1
-                    0
+                    player.score += sc_asset.scoring.drop_points;
+                    combo.on_drop(&sc_asset.combo);
                 },
-                BallSpawnEvent::Combine(_, level) => {
-                    let level_combined = level.map(|l| l.0-1)
-                        .unwrap_or(sc_asset.get_ball_max_level().0);
-                    level_combined.pow(2) as u32 // * 1
+                BallSpawnEvent::Combine(pos, level) => {
+                    let scoring = &sc_asset.scoring;
+                    let (level_combined, bonus) = match level {
+                        Some(level) => (BallLevel::new(level.0 - 1), 0),
+                        None => (sc_asset.get_ball_max_level(), scoring.max_level_bonus),
+                    };
+                    let chain = combo.on_combine(play_frame.0, &sc_asset.combo);
+                    let score = scoring.get_combine_points(level_combined) as f32 * sc_asset.combo.multiplier(chain);
+                    player.score += score.round() as u32 + bonus;
+                    if chain >= 2 {
+                        ev_combo.send(combo::ComboEvent { pos: *pos, chain });
+                    }
                 },
-            })
-            .sum();
-        player.score += score;
+            }
+        }
+    }
+}
+
+/// Adds `ScoringDef`'s end bonus for the balls left.
+fn add_end_bonus(
+    mut q_player: Query<&mut Player>,
+    q_balls: Query<&Ball>,
+    sc_asset: Res<GameAssets>,
+) {
+    if let Ok(mut player) = q_player.get_single_mut() {
+        let bonus = sc_asset.scoring.get_end_bonus(q_balls.iter().map(|b| b.level));
+        if bonus > 0 {
+            info!("End bonus: {}", bonus);
+        }
+        player.score += bonus;
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn check_game_over(
     mut commands: Commands,
     mut next_state: ResMut<NextState<GameScreenState>>,
-    q_balls: Query<(Entity, &Transform), With<Ball>>,
+    q_balls: Query<(
+        Entity,
+        &Transform,
+        Has<DroppingBall>,
+        Has<BallGrowing>,
+        Option<&overflow::RestingSince>,
+        Option<&OutOfAreaSince>,
+    ), With<Ball>>,
+    play_frame: Res<PlayFrame>,
     assets: Res<GameAssets>,
 ) {
-    let game_ron::Area { min_x, max_x, min_y, max_y } = assets.physics.area;
-    if let Some((entity, ball)) = q_balls.iter().find(|(_, t)| {
-        let t = t.translation;
-        let x = t.x;
-        let y = t.y;
-        !(min_x..=max_x).contains(&x) ||
-            !(min_y..=max_y).contains(&y)
-    }) {
-        info!("Game over: {:?} / {:?}", ball, assets.physics.area);
-        commands.entity(entity)
-            .insert(AreaProtruded);
-        next_state.set(GameScreenState::GameOver);
+    let grace_frames = (assets.physics.game_over_grace_sec as f64 * FIXED_TIMESTEP_HZ).round() as u64;
+    for (entity, ball, is_dropping, is_growing, resting, since) in q_balls.iter() {
+        let status = overflow::BallStatus {
+            pos: ball.translation.truncate(),
+            is_dropping,
+            is_growing,
+            rest_sec: overflow::RestingSince::rest_sec(resting, play_frame.0),
+        };
+        let is_out = overflow::is_overflowing(&status, &assets.physics.area, &assets.physics.overflow);
+        match (is_out, since) {
+            (false, None) => {},
+            (false, Some(_)) => {
+                commands.entity(entity)
+                    .remove::<OutOfAreaSince>();
+            },
+            (true, since) => {
+                let since = since.map_or(play_frame.0, |s| s.0);
+                if play_frame.0 - since >= grace_frames {
+                    info!("Game over: {:?} / {:?}", ball, assets.physics.area);
+                    commands.entity(entity)
+                        .insert(AreaProtruded);
+                    next_state.set(GameScreenState::GameOver);
+                    return;
+                } else if since == play_frame.0 {
+                    commands.entity(entity)
+                        .insert(OutOfAreaSince(since));
+                }
+            },
+        }
     }
 }
 
 
 /// Player inputs
 #[derive(Event, Debug, Clone, Copy, PartialEq)]
-enum PlayerInputEvent {
+#[derive(Deserialize, Serialize)]
+pub enum PlayerInputEvent {
     Drop,
     Move(f32), // [-1, 1]
     Hold,
@@ -585,11 +940,20 @@ enum PlayerInputEvent {
     Pause,
 }
 
+/// A horizontal shake while moving, a vertical one otherwise.
+fn shake_input(lr: f32) -> PlayerInputEvent {
+    if lr.abs() >= 0.5 {
+        PlayerInputEvent::Shake(Vec2::new(lr.signum(), 0.))
+    } else {
+        PlayerInputEvent::Shake(Vec2::new(0., 1.))
+    }
+}
+
 fn read_keyboard_for_player_actions(
     q_player: Query<&Player>,
     keyboard: Res<ButtonInput<KeyCode>>,
 
-    mut ev_player_act: EventWriter<PlayerInputEvent>,
+    mut input_queue: ResMut<PlayerInputQueue>,
 ) {
     if q_player.get_single().is_ok() {
         let mut lr = 0.;
@@ -600,24 +964,22 @@ fn read_keyboard_for_player_actions(
             lr += 1.;
         }
 
-        if lr != 0. {
-            ev_player_act.send(PlayerInputEvent::Move(lr));
-        }
+        input_queue.add_move(lr);
 
         if keyboard.any_just_pressed(KEYBOARD_KEYS_MAIN) {
-            ev_player_act.send(PlayerInputEvent::Drop);
+            input_queue.push(PlayerInputEvent::Drop);
         }
 
         if keyboard.any_just_pressed(KEYBOARD_KEYS_SUB1) {
-            ev_player_act.send(PlayerInputEvent::Hold);
+            input_queue.push(PlayerInputEvent::Hold);
         }
 
         if keyboard.any_just_pressed(KEYBOARD_KEYS_SUB2) {
-            ev_player_act.send(PlayerInputEvent::Shake(Vec2::new(0., 1.)));
+            input_queue.push(shake_input(lr));
         }
 
         if keyboard.any_just_pressed(KEYBOARD_KEYS_START) {
-            ev_player_act.send(PlayerInputEvent::Pause);
+            input_queue.push(PlayerInputEvent::Pause);
         }
     }
 }
@@ -630,7 +992,7 @@ fn read_gamepad_for_player_actions(
     axes: Res<Axis<GamepadAxis>>,
     buttons: Res<ButtonInput<GamepadButton>>,
 
-    mut ev_player_act: EventWriter<PlayerInputEvent>,
+    mut input_queue: ResMut<PlayerInputQueue>,
 ) {
     if q_player.get_single().is_ok() {
         if let Some(&ConnectedGamePad(gamepad)) = connected_gamepad.as_deref() {
@@ -658,24 +1020,22 @@ fn read_gamepad_for_player_actions(
                 lr += 1.;
             }
 
-            if lr != 0. {
-                ev_player_act.send(PlayerInputEvent::Move(lr.clamp(-1., 1.)));
-            }
+            input_queue.add_move(lr);
 
             if buttons.any_just_pressed(button(&GAMEPAD_BTNS_MAIN)) {
-                ev_player_act.send(PlayerInputEvent::Drop);
+                input_queue.push(PlayerInputEvent::Drop);
             }
 
             if buttons.any_just_pressed(button(&GAMEPAD_BTNS_SUB1)) {
-                ev_player_act.send(PlayerInputEvent::Hold);
+                input_queue.push(PlayerInputEvent::Hold);
             }
 
             if buttons.any_just_pressed(button(&GAMEPAD_BTNS_SUB2)) {
-                ev_player_act.send(PlayerInputEvent::Shake(Vec2::new(0., 1.)));
+                input_queue.push(shake_input(lr));
             }
 
             if buttons.any_just_pressed(button(&GAMEPAD_BTNS_START)) {
-                ev_player_act.send(PlayerInputEvent::Pause);
+                input_queue.push(PlayerInputEvent::Pause);
             }
         }
     }
@@ -693,10 +1053,8 @@ struct DroppingBallGuideBody;
 /// spwans player / puppetter / guide for dropping a ball
 fn spawn_player(
     mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-
-    mut global_ent: ResMut<GlobalEntropy<ChaCha8Rng>>,
+    play_seed: Res<PlaySeed>,
+    puzzle: Option<Res<Puzzle>>,
     assets: Res<GameAssets>,
 ) {
     let player_y_max = assets.bottle_settings.left_top().y + PLAYER_GAP_WALL + PLAYER_GAP_TO_MAX;
@@ -716,10 +1074,36 @@ fn spawn_player(
 
     // player
     let player_y = assets.bottle_settings.left_top().y + PLAYER_GAP_WALL;
-    let mut player = Player::new(assets.player_settings.speed, BallLevel::new(1), assets.drop_ball_level_max);
-    let mut rng = global_ent.fork_rng();
-    player.set_next_ball_level_from_rng(&mut rng);
+    let mut player = Player::new(assets.player_settings.speed, BallLevel::new(1), assets.drop_ball_level_max)
+        .with_next_ball(assets.next_ball.clone())
+        .with_preview_len(assets.preview_len())
+        .with_hold(assets.hold.clone());
+    if let Some(puzzle) = puzzle {
+        player = player.with_ball_queue(puzzle.next_ball_levels());
+    }
+    let mut rng = EntropyComponent::<ChaCha8Rng>::seed_from_u64(play_seed.0);
+    player.set_next_ball_level(&mut rng);
 
+    commands.spawn((
+        player,
+        SpatialBundle {
+            transform: Transform::from_translation(
+                Vec2::new(0., player_y).extend(Z_PLAYER)),
+            ..default()
+        },
+        rng,
+    ));
+}
+
+fn spawn_player_view(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    q_player: Query<(Entity, &Transform), With<Player>>,
+    assets: Res<GameAssets>,
+) {
+    let Ok((player, player_trans)) = q_player.get_single() else { return; };
+    let player_y = player_trans.translation.y;
 
     let player_material = materials.add(assets.player_settings.h_image.clone());
     let player_mesh = Rectangle::new(
@@ -731,15 +1115,7 @@ fn spawn_player(
         assets.player_settings.offset_y,
     );
 
-    commands.spawn((
-        player,
-        SpatialBundle {
-            transform: Transform::from_translation(
-                Vec2::new(0., player_y).extend(Z_PLAYER)),
-            ..default()
-        },
-        rng,
-    )).with_children(|b| {
+    commands.entity(player).with_children(|b| {
         b.spawn((
             MaterialMesh2dBundle {
                 mesh: Mesh2dHandle(meshes.add(player_mesh)),
@@ -784,10 +1160,10 @@ fn move_puppeteer(
         if let Ok(player) = q_player.get_single() {
             for ev in ev_player_act.read() {
                 if let PlayerInputEvent::Move(lr) = ev {
-                    let bottle_width = assets.bottle_settings.inner_width;
+                    let (min_x, max_x) = assets.bottle_settings.opening_x();
                     trans.translation.x =
                         (trans.translation.x + lr * player.speed)
-                            .clamp(-bottle_width/2., bottle_width/2.);
+                            .clamp(min_x, max_x);
                 }
             }
         }
@@ -834,8 +1210,8 @@ fn sync_puppetter_shape_caster(
 ) {
     if let Ok(player) = q_player.get_single() {
         if let Ok(mut shape_caster) = q_shape_caster.get_single_mut() {
-            let r = assets.get_ball_r(player.next_ball_level);
-            shape_caster.shape = Collider::circle(r);
+            let level = player.next_ball_level;
+            shape_caster.shape = create_ball_collider(level, assets.get_ball_r(level), &assets);
         }
     }
 }
@@ -881,7 +1257,7 @@ fn action_player(
         for ev in ev_player_act.read() {
             match ev {
                 PlayerInputEvent::Drop => {
-                    if player.can_drop {
+                    if player.can_drop && !player.is_out_of_balls {
                         let pos = trans.translation.xy();
                         let lv = player.next_ball_level;
 
@@ -891,20 +1267,13 @@ fn action_player(
 
                         ev_ball_spawn.send(BallSpawnEvent::Drop(pos + Vec2::X * jitter, lv));
 
-                        player.set_next_ball_level_from_rng(&mut rng);
+                        player.set_next_ball_level(&mut rng);
                         player.can_drop = false;
+                        player.is_hold_locked = false;
                     }
                 },
                 PlayerInputEvent::Hold => {
-                    if player.can_drop {
-                        let lv = player.next_ball_level;
-                        if let Some(hold_level) = player.hold_ball {
-                            player.next_ball_level = hold_level;
-                        } else {
-                            player.set_next_ball_level_from_rng(&mut rng);
-                        }
-                        player.hold_ball = Some(lv);
-                    }
+                    player.hold(&mut rng);
                 }
                 PlayerInputEvent::Move(_lr) => {
                 },
@@ -917,47 +1286,70 @@ fn action_player(
     }
 }
 
-#[derive(Component, Debug)]
-struct Shaking(Vec<(Vec2, Timer)>);
+/// Shakes of the bottle in progress and ones done in the game.
+#[derive(Component, Debug, Default)]
+struct Shaking {
+    shakes: Vec<(Vec2, Timer)>,
+    count: u32,
+    last_frame: Option<u64>,
+}
+
+impl Shaking {
+    fn can_shake(&self, frame: u64, def: &ShakeDef) -> bool {
+        let is_cooled_down = self.last_frame.map_or(true, |last|
+            (frame - last) as f64 >= def.cooldown_sec as f64 * FIXED_TIMESTEP_HZ);
+        let is_under_limit = def.max_shakes.map_or(true, |max| self.count < max);
+        is_cooled_down && is_under_limit
+    }
+
+    /// Offset of the bottle from its origin and its tilt.
+    /// `Shake(v)` moves the bottle to `(v.x, -v.y)` and back.
+    fn offset(&self, def: &ShakeDef, shake_k: f32) -> (Vec2, f32) {
+        let max = shake_k * shake_y(0.5);
+        let (offset, tilt) = self.shakes.iter()
+            .map(|(v, t)| (Vec2::new(v.x, -v.y), shake_y(t.fraction()) / shake_y(0.5)))
+            .fold((Vec2::ZERO, 0.), |(offset, tilt), (v, c)| (offset + v * c * max, tilt - v.x * c * def.tilt));
+        (offset.clamp(Vec2::splat(-max), Vec2::splat(max)), tilt.clamp(-def.tilt.abs(), def.tilt.abs()))
+    }
+}
 
 fn shake_y(s: f32) -> f32 {
     let x = s*8. - 4.;
     (1. / (2.*PI).sqrt()) * std::f32::consts::E.powf(- x*x / 2.)
 }
 
+/// Moves the bottle by its velocity to push balls consistently.
 fn shake_bottle(
-    mut commands: Commands,
-    mut q_bottle: Query<(Entity, &Bottle, &mut Transform, Option<&mut Shaking>)>,
+    mut q_bottle: Query<(&Bottle, &Transform, &mut Shaking, &mut LinearVelocity, &mut AngularVelocity)>,
     mut ev_player_act: EventReader<PlayerInputEvent>,
+    play_frame: Res<PlayFrame>,
     time: Res<Time>,
     assets: Res<GameAssets>,
 ) {
+    let Ok((bottle, bottle_trans, mut shaking, mut velocity, mut angular_velocity)) = q_bottle.get_single_mut() else {
+        return;
+    };
+    let def = &assets.shake;
     let delta = time.delta();
-    let new_shakes = ev_player_act.read()
-        .filter_map(|ev| {
-            if let PlayerInputEvent::Shake(v) = ev {
-                Some(v)
-            } else {
-                None
+    shaking.shakes.iter_mut().for_each(|(_, t)| {t.tick(delta);});
+    shaking.shakes.retain(|(_, t)| !t.finished());
+    for ev in ev_player_act.read() {
+        if let PlayerInputEvent::Shake(v) = ev {
+            if shaking.can_shake(play_frame.0, def) {
+                shaking.shakes.push((*v, Timer::from_seconds(def.duration_sec, TimerMode::Once)));
+                shaking.count += 1;
+                shaking.last_frame = Some(play_frame.0);
             }
-        })
-        .map(|&v| (v, Timer::from_seconds(1.0, TimerMode::Once)));
-    if let Ok((bottle_entity, bottle, mut bottle_trans, shaking)) = q_bottle.get_single_mut() {
-        let iter = if let Some(mut shaking) = shaking {
-            shaking.0.iter_mut().for_each(|t| {t.1.tick(delta);});
-            shaking.0.retain(|(_,t)| !t.finished());
-            shaking.0.append(&mut new_shakes.collect());
-            shaking.0.clone()
-        } else {
-            let shakes = new_shakes.collect_vec();
-            commands.entity(bottle_entity)
-                .insert(Shaking(shakes.clone()));
-            shakes
-        };
-
-        let max_y = assets.physics.shake_k * iter.iter().map(|(v,t)| shake_y(v.y * t.elapsed_secs())).reduce(f32::max).unwrap_or(0.);
+        }
+    }
 
-        bottle_trans.translation.y = bottle.origin.y - max_y;
+    let delta_sec = time.delta_seconds();
+    if delta_sec > 0. {
+        let (offset, tilt) = shaking.offset(def, assets.physics.shake_k);
+        let target = bottle.origin + offset;
+        velocity.0 = (target - bottle_trans.translation.xy()) / delta_sec;
+        let angle = bottle_trans.rotation.to_euler(EulerRot::XYZ).2;
+        angular_velocity.0 = (tilt - angle) / delta_sec;
     }
 }
 
@@ -976,11 +1368,10 @@ struct ScoreText;
 fn spawn_score_view(
     mut commands: Commands,
     my_assets: Res<GameAssets>,
-    config: Res<Config>,
+    play_cond: Res<PlayCond>,
     scores: Res<Scores>,
 ) {
-    let game_cnd = GameCond::new(&config.game_ron_name);
-    let highscore = scores.get_highest(&game_cnd);
+    let highscore = scores.get_highest(&play_cond.0);
     let high_score_txt = format!("high score:{:>8}", highscore.unwrap_or(&default()).score);
 
 
@@ -1037,7 +1428,7 @@ fn spawn_score_view(
             };
             b.spawn((
                 Text2dBundle {
-                    text: Text::from_section("SCORE", text_style.clone()),
+                    text: Text::from_section(play_cond.0.game_mode.get_score_label(), text_style.clone()),
                     transform: Transform::from_translation(label_pos.extend(0.01)),
                     ..default()
                 },
@@ -1190,18 +1581,37 @@ fn spawn_manual_view(
 
 #[derive(Component, Debug)]
 struct HoldingBallView;
+/// (index of hold slots, level shown)
+#[derive(Component, Debug)]
+struct HoldingBallImage(usize, Option<BallLevel>);
 #[derive(Component, Debug)]
-struct HoldingBallImage(Option<BallLevel>);
+struct HoldingLabelText;
+
+const HOLD_VIEW_INNER_MARGIN: f32 = 4.;
+
+/// Size of the space for a held ball.
+fn hold_slot_size(my_assets: &GameAssets) -> Vec2 {
+    let size = my_assets.hold_view_size();
+    let border_width = my_assets.ui.hold_view.border_width;
+    Vec2::new(
+        (size.x - border_width * 2.) / my_assets.hold.slots.max(1) as f32,
+        size.y - border_width * 2. - FONT_WEIGHT_L - HOLD_VIEW_INNER_MARGIN * 2.,
+    )
+}
 
 fn spwan_holding_ball_view(
     mut commands: Commands,
     my_assets: Res<GameAssets>,
 ) {
+    if my_assets.hold.slots == 0 {
+        return; // Holding is disabled.
+    }
     let border_width = my_assets.ui.hold_view.border_width;
-    let inner_margin = 4.;
+    let inner_margin = HOLD_VIEW_INNER_MARGIN;
     let label_weight = FONT_WEIGHT_L;
     let plus_weight = FONT_WEIGHT_S;
     let size = my_assets.hold_view_size();
+    let slot_size = hold_slot_size(&my_assets);
     commands.spawn((
         HoldingBallView,
         SpriteBundle {
@@ -1225,8 +1635,6 @@ fn spwan_holding_ball_view(
         let label_pos =
             Vec2::new(0., size.y/2.- label_weight/2. - border_width - inner_margin)
             ;
-        let image_pos =
-            Vec2::new(0., -label_weight/2.);
         let text_style = TextStyle {
             font: my_assets.h_font.clone(),
             font_size: label_weight,
@@ -1238,35 +1646,42 @@ fn spwan_holding_ball_view(
             color: my_assets.ui.hold_view.font_color,
         };
         b.spawn((
+            HoldingLabelText,
             Text2dBundle {
                 text: Text::from_sections([
                     TextSection::new("Hold", text_style.clone()),
                     TextSection::new(format!("[{}]", GpKbInput::Sub1.get_str()), text_style_p.clone()),
+                    // Holds left
+                    TextSection::new("", text_style_p.clone()),
                 ]),
                 transform: Transform::from_translation(label_pos.extend(0.02)),
                 ..default()
             },
         ));
 
-        b.spawn((
-            SpatialBundle {
-                transform: Transform::from_translation(
-                               image_pos.extend(0.01)
-                           ),
-                ..default()
-            },
-        ))
-        .with_children(|b| {
+        for i in 0..my_assets.hold.slots {
+            let image_pos =
+                Vec2::new(-slot_size.x * (my_assets.hold.slots as f32 / 2. - i as f32 - 0.5), -label_weight/2.);
             b.spawn((
-                HoldingBallImage(None),
                 SpatialBundle {
                     transform: Transform::from_translation(
-                                   Vec2::ZERO.extend(0.01)
+                                   image_pos.extend(0.01)
                                ),
                     ..default()
-                }
-            ));
-        });
+                },
+            ))
+            .with_children(|b| {
+                b.spawn((
+                    HoldingBallImage(i, None),
+                    SpatialBundle {
+                        transform: Transform::from_translation(
+                                       Vec2::ZERO.extend(0.01)
+                                   ),
+                        ..default()
+                    }
+                ));
+            });
+        }
     });
 }
 
@@ -1285,6 +1700,7 @@ fn update_player_view(
     q_holding_ball: Query<(Entity, &HoldingBallImage)>,
 
     mut q_score_text: Query<&mut Text, With<ScoreText>>,
+    mut q_hold_text: Query<&mut Text, (With<HoldingLabelText>, Without<ScoreText>)>,
 
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
@@ -1332,22 +1748,42 @@ fn update_player_view(
         }
 
         // Holding
-        if let Ok((hold_ball_entity, hold_ball)) = q_holding_ball.get_single() {
-            if hold_ball.0 != player.hold_ball {
-                if let Some(hold_ball) = player.hold_ball {
+        let slot_size = hold_slot_size(&my_assets);
+        for (hold_ball_entity, HoldingBallImage(idx, shown)) in q_holding_ball.iter() {
+            let hold_ball = player.hold_balls.get(*idx).copied();
+            if hold_ball != *shown {
+                if let Some(hold_ball) = hold_ball {
+                    // Large balls are shrunk to fit the slot.
+                    let (w, h) = my_assets.get_ball_mesh_wh(hold_ball);
+                    let scale = (slot_size.x / w).min(slot_size.y / h).min(1.);
                     let ball_view = create_ball_view(
                         &mut meshes, &mut materials, hold_ball,
                         Vec2::ZERO, &my_assets);
                     commands.entity(hold_ball_entity)
-                        .insert(HoldingBallImage(Some(hold_ball)))
-                        .insert(ball_view);
+                        .insert(HoldingBallImage(*idx, Some(hold_ball)))
+                        .insert(ball_view)
+                        .insert(Transform::from_translation(
+                                Vec2::ZERO.extend(Z_BALL + Z_BALL_D_BY_LEVEL * hold_ball.0 as f32))
+                            .with_scale(Vec3::splat(scale)));
                 } else {
                     commands.entity(hold_ball_entity)
-                        .insert(HoldingBallImage(None))
+                        .insert(HoldingBallImage(*idx, None))
                         .insert(Visibility::Hidden);
                 }
             }
         }
+        if let Ok(mut text) = q_hold_text.get_single_mut() {
+            let mut color = my_assets.ui.hold_view.font_color;
+            if player.is_hold_locked || player.holds_left == Some(0) {
+                color.set_a(color.a() * 0.3);
+            }
+            for section in text.sections.iter_mut() {
+                section.style.color = color;
+            }
+            if let (Some(section), Some(n)) = (text.sections.get_mut(2), player.holds_left) {
+                section.value = format!(" x{}", n);
+            }
+        }
 
         // Score
         if let Ok(mut text) = q_score_text.get_single_mut() {
@@ -1392,6 +1828,23 @@ impl BallGrowing {
     }
 }
 
+fn create_ball_mesh_material(
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+
+    level: BallLevel,
+
+    my_assets: &Res<GameAssets>,
+) -> (Mesh2dHandle, Handle<ColorMaterial>) {
+    let ball_material = materials.add(my_assets.get_ball_image(level).clone());
+    let (mesh_w, mesh_h) = my_assets.get_ball_mesh_wh(level);
+    // Aligned to the shape
+    let offset = my_assets.get_ball_setting(level).view_offset;
+    let mesh = Mesh::from(Rectangle::new(mesh_w, mesh_h))
+        .translated_by(offset.extend(0.));
+    (meshes.add(mesh).into(), ball_material)
+}
+
 fn create_ball_view_base(
     meshes: &mut ResMut<Assets<Mesh>>,
     materials: &mut ResMut<Assets<ColorMaterial>>,
@@ -1402,14 +1855,13 @@ fn create_ball_view_base(
     my_assets: &Res<GameAssets>,
 ) -> MaterialMesh2dBundle<ColorMaterial> {
 
-    let ball_material = materials.add(my_assets.get_ball_image(level).clone());
-    let (mesh_w, mesh_h) = my_assets.get_ball_mesh_wh(level);
+    let (mesh, material) = create_ball_mesh_material(meshes, materials, level, my_assets);
     MaterialMesh2dBundle {
-        mesh: meshes.add(Rectangle::new(mesh_w, mesh_h)).into(),
+        mesh,
         transform: Transform::from_translation(
              pos.extend(0.0)
         ),
-        material: ball_material,
+        material,
         ..default()
     }
 }
@@ -1442,51 +1894,103 @@ fn create_ball_view(
     b
 }
 
+fn create_ball_spatial(
+    level: BallLevel,
+    pos: Vec2,
+) -> SpatialBundle {
+    SpatialBundle {
+        transform: Transform::from_translation(
+            pos.extend(Z_BALL + Z_BALL_D_BY_LEVEL * level.0 as f32)
+        ),
+        ..default()
+    }
+}
+
+/// The shape of the ball of `level` scaled to the radius `r`.
+fn create_ball_collider(
+    level: BallLevel,
+    r: f32,
+    my_assets: &GameAssets,
+) -> Collider {
+    use game_ron::BallShapeRon::*;
+    match &my_assets.get_ball_setting(level).shape {
+        Circle => Collider::circle(r),
+        Ellipse { width, height, vertices } => {
+            let points = (0..*vertices)
+                .map(|i| {
+                    let t = 2. * PI * i as f32 / *vertices as f32;
+                    Vec2::new(width * t.cos(), height * t.sin()) * r / 2.
+                })
+                .collect();
+            Collider::convex_hull(points)
+                .unwrap_or_else(|| Collider::circle(r))
+        },
+        RoundedPolygon { vertices, radius } => {
+            let points = vertices.iter().map(|v| *v * r).collect();
+            Collider::round_convex_hull(points, radius * r)
+                .unwrap_or_else(|| Collider::circle(r))
+        },
+        Capsule { length, radius } => Collider::capsule(length * r, radius * r),
+        Circles(circles) => Collider::compound(
+            circles.iter()
+                .map(|(c, cr)| (Position(*c * r), Rotation::default(), Collider::circle(cr * r)))
+                .collect()
+        ),
+    }
+}
+
+/// Physical properties of the ball of `level` merged with `ball_physics`.
+fn create_ball_physics_param(
+    level: BallLevel,
+    my_assets: &GameAssets,
+) -> (Restitution, Friction, ColliderDensity, LinearDamping, AngularDamping) {
+    let base = &my_assets.ball_physics;
+    let physics = &my_assets.get_ball_setting(level).physics;
+    let friction = physics.friction.as_ref();
+    (
+        Restitution {
+            coefficient: physics.restitution.as_ref()
+                .map_or(base.restitution.coef, |r| r.coef),
+            ..default()
+        },
+        Friction {
+            dynamic_coefficient: friction.map_or(base.friction.dynamic_coef, |f| f.dynamic_coef),
+            static_coefficient: friction.map_or(base.friction.static_coef, |f| f.static_coef),
+            ..default()
+        },
+        ColliderDensity(my_assets.get_ball_density(level)),
+        LinearDamping(physics.linear_damping.unwrap_or(0.)),
+        AngularDamping(physics.angular_damping.unwrap_or(0.)),
+    )
+}
+
 fn spawn_ball(
     mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-
     mut ev_ball_spawn: EventReader<BallSpawnEvent>,
     my_assets: Res<GameAssets>,
 ) {
     for ev in ev_ball_spawn.read() {
         use BallSpawnEvent::*;
-        let physics_param = (
-            Restitution {
-                coefficient: my_assets.ball_physics.restitution.coef,
-                ..default()
-            },
-            Friction {
-                dynamic_coefficient: my_assets.ball_physics.friction.dynamic_coef,
-                static_coefficient: my_assets.ball_physics.friction.static_coef,
-                ..default()
-            },
-        );
         match *ev {
             Drop(pos, level) => {
-                let ball_view = create_ball_view(&mut meshes, &mut materials,
-                                                 level, pos, &my_assets);
                 commands.spawn((
                     DroppingBall,
                     Ball::new(level),
                     RigidBody::Dynamic,
-                    Collider::circle(my_assets.get_ball_r(level)),
-                    physics_param,
-                    ball_view,
+                    create_ball_collider(level, my_assets.get_ball_r(level), &my_assets),
+                    create_ball_physics_param(level, &my_assets),
+                    create_ball_spatial(level, pos),
                 ));
             },
             Combine(pos, Some(level)) => {
                 let ball_r_start = my_assets.get_ball_start_r(level);
-                let ball_view = create_ball_view(&mut meshes, &mut materials,
-                                                 level, pos, &my_assets);
                 commands.spawn((
                     Ball::new(level),
                     RigidBody::Dynamic,
-                    Collider::circle(ball_r_start),
+                    create_ball_collider(level, ball_r_start, &my_assets),
                     BallGrowing::new(my_assets.physics.ball_grow_time),
-                    physics_param,
-                    ball_view,
+                    create_ball_physics_param(level, &my_assets),
+                    create_ball_spatial(level, pos),
                 ));
             },
             Combine(_, None) => {
@@ -1496,6 +2000,21 @@ fn spawn_ball(
     }
 }
 
+/// Gives a view to each ball spawned by `spawn_ball`.
+fn spawn_ball_view(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    q_ball: Query<(Entity, &Ball), Added<Ball>>,
+    my_assets: Res<GameAssets>,
+) {
+    for (entity, ball) in q_ball.iter() {
+        let view = create_ball_mesh_material(&mut meshes, &mut materials,
+                                             ball.level, &my_assets);
+        commands.entity(entity).insert(view);
+    }
+}
+
 fn check_dropping_ball(
     mut commands: Commands,
     mut q_player: Query<&mut Player, Without<DroppingBall>>,
@@ -1543,10 +2062,9 @@ fn grow_ball_spawned(
         spacer.sec += time.delta_seconds();
 
         if spacer.sec > spacer.sec_max {
+            let level = *ball.get_level();
             commands.entity(entity)
-                .try_insert(Collider::circle(
-                        sc_asset.get_ball_r(*ball.get_level())
-                    ))
+                .try_insert(create_ball_collider(level, sc_asset.get_ball_r(level), &sc_asset))
                 .remove::<BallGrowing>();
         } else {
             let level = *ball.get_level();
@@ -1554,7 +2072,7 @@ fn grow_ball_spawned(
             let r_from = sc_asset.get_ball_start_r(level);
             let r = (spacer.sec / spacer.sec_max) * (r_to - r_from) + r_from;
             commands.entity(entity)
-                .try_insert(Collider::circle(r));
+                .try_insert(create_ball_collider(level, r, &sc_asset));
         }
     }
 }
@@ -1582,11 +2100,15 @@ fn cleanup_ingame_entites(
             With<PlayerPuppeteer>,
             With<DroppingBallGuide>,
             With<HoldingBallView>,
+            With<preview::PreviewView>,
             With<ManualView>,
             With<Ball>,
             With<Bottle>,
+            With<obstacles::RotatingObstacle>,
             With<Background>,
             With<ScoreView>,
+            With<time_attack::CountdownView>,
+            With<mission::MissionView>,
             With<effects::Effect>,
         )>>,
 ) {
@@ -0,0 +1,122 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use game_ron::ChainRon;
+
+use super::effects;
+use super::FIXED_TIMESTEP_HZ;
+
+/// Chain of combines in the current game.
+#[derive(Resource, Debug, Default)]
+pub struct Combo {
+    /// Number of combines in the current chain. 0 if no chain.
+    pub chain: u32,
+    last_combine_frame: u64,
+    pub stats: ComboStats,
+}
+
+impl Combo {
+    pub fn on_drop(&mut self, def: &ComboDef) {
+        if let ChainRon::SameDrop = def.chain {
+            self.chain = 0;
+        }
+    }
+
+    /// Returns the number of combines in the chain including this one.
+    pub fn on_combine(&mut self, frame: u64, def: &ComboDef) -> u32 {
+        if let ChainRon::TimeWindow(sec) = def.chain {
+            let elapsed = frame.saturating_sub(self.last_combine_frame) as f64 / FIXED_TIMESTEP_HZ;
+            if elapsed > sec as f64 {
+                self.chain = 0;
+            }
+        }
+        self.chain += 1;
+        self.last_combine_frame = frame;
+
+        if self.chain == 2 {
+            self.stats.chains += 1;
+        }
+        self.stats.max_chain = self.stats.max_chain.max(self.chain);
+        self.chain
+    }
+}
+
+pub fn reset_combo(
+    mut combo: ResMut<Combo>,
+) {
+    *combo = default();
+}
+
+/// A combine chained to previous ones.
+#[derive(Event, Debug)]
+pub struct ComboEvent {
+    pub pos: Vec2,
+    pub chain: u32,
+}
+
+pub fn spawn_combo_popups(
+    mut commands: Commands,
+    mut ev_combo: EventReader<ComboEvent>,
+    my_assets: Res<GameAssets>,
+) {
+    for ev in ev_combo.read() {
+        let text_style = TextStyle {
+            font: my_assets.h_font.clone(),
+            font_size: super::FONT_WEIGHT_L,
+            color: my_assets.ui.score_view.font_color,
+        };
+        effects::spawn_text_effect(
+            ev.pos,
+            &mut commands,
+            Text::from_section(format!("{} Combo!", ev.chain), text_style),
+        );
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combines_in_time_window_are_chained() {
+        let def = ComboDef {
+            chain: ChainRon::TimeWindow(1.0),
+            multipliers: vec![],
+        };
+        let window = FIXED_TIMESTEP_HZ as u64;
+        let mut combo = Combo::default();
+        assert_eq!(combo.on_combine(100, &def), 1);
+        assert_eq!(combo.on_combine(100 + window, &def), 2);
+        combo.on_drop(&def);
+        assert_eq!(combo.on_combine(100 + window * 2, &def), 3);
+        assert_eq!(combo.on_combine(100 + window * 3 + 1, &def), 1);
+        assert_eq!(combo.stats, ComboStats { max_chain: 3, chains: 1 });
+    }
+
+    #[test]
+    fn combines_by_same_drop_are_chained() {
+        let def = ComboDef {
+            chain: ChainRon::SameDrop,
+            multipliers: vec![],
+        };
+        let mut combo = Combo::default();
+        assert_eq!(combo.on_combine(0, &def), 1);
+        assert_eq!(combo.on_combine(1000, &def), 2);
+        combo.on_drop(&def);
+        assert_eq!(combo.on_combine(1001, &def), 1);
+        assert_eq!(combo.on_combine(1002, &def), 2);
+        assert_eq!(combo.stats, ComboStats { max_chain: 2, chains: 2 });
+    }
+
+    #[test]
+    fn combo_multiplier_uses_last_one_for_long_chains() {
+        let def = ComboDef {
+            chain: ChainRon::SameDrop,
+            multipliers: vec![1.0, 1.5, 2.0],
+        };
+        assert_eq!(def.multiplier(1), 1.0);
+        assert_eq!(def.multiplier(3), 2.0);
+        assert_eq!(def.multiplier(10), 2.0);
+        assert_eq!(ComboDef::default().multiplier(5), 1.0);
+    }
+}
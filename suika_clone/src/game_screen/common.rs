@@ -1,9 +1,12 @@
+use std::collections::VecDeque;
+
 use crate::prelude::*;
 use bevy::prelude::*;
 
+use super::next_ball::NextBallPicker;
+
 use bevy_rand::prelude::*;
 use bevy_prng::ChaCha8Rng;
-use rand_core::RngCore;
 
 #[derive(States, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum GameScreenState {
@@ -13,6 +16,8 @@ pub enum GameScreenState {
     Playing,
     Paused,
     GameOver,
+    /// All missions are met.
+    Cleared,
     Restart,
 }
 
@@ -20,9 +25,24 @@ pub enum GameScreenState {
 pub struct Player {
     pub speed: f32,
     pub next_ball_level: BallLevel,
-    pub max_ball_level: BallLevel,
+    pub next_ball_picker: NextBallPicker,
+    /// Levels after `next_ball_level` picked ahead. Not used with `ball_queue`.
+    pub preview_ball_levels: VecDeque<BallLevel>,
+    /// Number of levels picked ahead.
+    pub preview_len: usize,
+
+    /// Balls held. The front one comes back first.
+    pub hold_balls: VecDeque<BallLevel>,
+    pub hold: HoldDef,
+    /// Holds left in the game. `None` is unlimited.
+    pub holds_left: Option<u32>,
+    /// Held since the last drop. Locks holding with `HoldDef::once_per_drop`.
+    pub is_hold_locked: bool,
 
-    pub hold_ball: Option<BallLevel>,
+    /// Levels of next balls in the puzzle mode. `None` is random.
+    pub ball_queue: Option<VecDeque<BallLevel>>,
+    /// No balls are left in `ball_queue` and `hold_balls`.
+    pub is_out_of_balls: bool,
 
     pub can_drop: bool,
 
@@ -34,9 +54,17 @@ impl Default for Player {
         Self {
             speed: 3.0,
             next_ball_level: default(),
-            max_ball_level: default(),
+            next_ball_picker: default(),
+            preview_ball_levels: default(),
+            preview_len: 0,
 
-            hold_ball: None,
+            hold_balls: default(),
+            hold: default(),
+            holds_left: None,
+            is_hold_locked: false,
+
+            ball_queue: None,
+            is_out_of_balls: false,
 
             can_drop: true,
 
@@ -49,17 +77,85 @@ impl Player {
         Self {
             speed,
             next_ball_level: first_ball_level,
-            max_ball_level,
+            next_ball_picker: NextBallPicker::new(NextBallDef::uniform(max_ball_level)),
             ..default()
         }
     }
-    pub fn set_next_ball_level_from_rng(&mut self, rng: &mut EntropyComponent<ChaCha8Rng>) {
-
-        self.next_ball_level = BallLevel::from_rand_u32(rng.next_u32(),
-            BallLevel::new(BALL_LEVEL_MIN), self.max_ball_level);
+    pub fn with_next_ball(mut self, def: NextBallDef) -> Self {
+        self.next_ball_picker = NextBallPicker::new(def);
+        self
+    }
+    pub fn with_preview_len(mut self, preview_len: usize) -> Self {
+        self.preview_len = preview_len;
+        self
+    }
+    pub fn with_hold(mut self, hold: HoldDef) -> Self {
+        self.holds_left = hold.max_holds;
+        self.hold = hold;
+        self
+    }
+    pub fn with_ball_queue(mut self, levels: impl IntoIterator<Item = BallLevel>) -> Self {
+        self.ball_queue = Some(levels.into_iter().collect());
+        self
+    }
+    /// From `ball_queue` if exists, otherwise from `rng`.
+    pub fn set_next_ball_level(&mut self, rng: &mut EntropyComponent<ChaCha8Rng>) {
+        if let Some(queue) = self.ball_queue.as_mut() {
+            // The ball held is the last one.
+            match queue.pop_front().or_else(|| self.hold_balls.pop_front()) {
+                Some(level) => self.next_ball_level = level,
+                None => self.is_out_of_balls = true,
+            }
+        } else {
+            self.set_next_ball_level_from_rng(rng);
+        }
+    }
+    fn set_next_ball_level_from_rng(&mut self, rng: &mut EntropyComponent<ChaCha8Rng>) {
+        // Levels are picked in the same order whatever `preview_len` is.
+        while self.preview_ball_levels.len() <= self.preview_len {
+            let level = self.next_ball_picker.pick(rng);
+            self.preview_ball_levels.push_back(level);
+        }
+        if let Some(level) = self.preview_ball_levels.pop_front() {
+            self.next_ball_level = level;
+        }
+    }
+    /// Up to `preview_len` levels after `next_ball_level`.
+    pub fn upcoming_ball_levels(&self) -> Vec<BallLevel> {
+        match &self.ball_queue {
+            Some(queue) => queue.iter().take(self.preview_len).copied().collect(),
+            None => self.preview_ball_levels.iter().copied().collect(),
+        }
+    }
+    pub fn can_hold(&self) -> bool {
+        self.hold.slots > 0
+            && self.can_drop
+            && !self.is_out_of_balls
+            && !self.is_hold_locked
+            && self.holds_left != Some(0)
+    }
+    /// Puts the next ball into the hold slots.
+    /// When all slots are used, the ball held first becomes the next ball.
+    pub fn hold(&mut self, rng: &mut EntropyComponent<ChaCha8Rng>) {
+        if !self.can_hold() {
+            return;
+        }
+        self.hold_balls.push_back(self.next_ball_level);
+        if self.hold_balls.len() > self.hold.slots {
+            if let Some(level) = self.hold_balls.pop_front() {
+                self.next_ball_level = level;
+            }
+        } else {
+            // With an empty `ball_queue`, the ball held comes back.
+            self.set_next_ball_level(rng);
+        }
+        self.is_hold_locked = self.hold.once_per_drop;
+        if let Some(n) = self.holds_left.as_mut() {
+            *n -= 1;
+        }
     }
     pub fn is_fakeball_exists(&self) -> bool {
-        self.can_drop
+        self.can_drop && !self.is_out_of_balls
     }
 }
 
@@ -81,3 +177,124 @@ impl Ball {
 
 #[derive(Component, Debug)]
 pub struct AreaProtruded;
+
+/// The frame when the ball started overflowing (see `overflow::is_overflowing`).
+#[derive(Component, Debug)]
+pub struct OutOfAreaSince(pub u64);
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::Itertools;
+    use rand_core::SeedableRng;
+
+    #[test]
+    fn held_ball_is_the_last_one_of_queue() {
+        let mut rng = EntropyComponent::<ChaCha8Rng>::seed_from_u64(0);
+        let mut player = Player::new(1., BallLevel(1), BallLevel(4))
+            .with_ball_queue([BallLevel(2), BallLevel(3)]);
+        player.set_next_ball_level(&mut rng);
+        player.hold_balls.push_back(BallLevel(4));
+
+        player.set_next_ball_level(&mut rng);
+        assert_eq!(player.next_ball_level, BallLevel(3));
+        player.set_next_ball_level(&mut rng);
+        assert_eq!(player.next_ball_level, BallLevel(4));
+        assert!(player.hold_balls.is_empty());
+        assert!(!player.is_out_of_balls);
+        player.set_next_ball_level(&mut rng);
+        assert!(player.is_out_of_balls);
+    }
+
+    #[test]
+    fn preview_does_not_change_next_balls() {
+        let next_levels = |preview_len: usize| {
+            let mut rng = EntropyComponent::<ChaCha8Rng>::seed_from_u64(0);
+            let mut player = Player::new(1., BallLevel(1), BallLevel(4))
+                .with_preview_len(preview_len);
+            (0..20).map(|_| {
+                player.set_next_ball_level(&mut rng);
+                assert_eq!(player.upcoming_ball_levels().len(), preview_len);
+                player.next_ball_level
+            }).collect_vec()
+        };
+        assert_eq!(next_levels(0), next_levels(3));
+    }
+
+    #[test]
+    fn preview_shows_levels_to_come() {
+        let mut rng = EntropyComponent::<ChaCha8Rng>::seed_from_u64(0);
+        let mut player = Player::new(1., BallLevel(1), BallLevel(4))
+            .with_preview_len(2);
+        player.set_next_ball_level(&mut rng);
+        let upcoming = player.upcoming_ball_levels();
+        player.set_next_ball_level(&mut rng);
+        assert_eq!(player.next_ball_level, upcoming[0]);
+        assert_eq!(player.upcoming_ball_levels()[0], upcoming[1]);
+
+        let player = Player::new(1., BallLevel(1), BallLevel(4))
+            .with_preview_len(2)
+            .with_ball_queue([BallLevel(2), BallLevel(3), BallLevel(4)]);
+        assert_eq!(player.upcoming_ball_levels(), [BallLevel(2), BallLevel(3)]);
+    }
+
+    fn player_with_hold(slots: usize, once_per_drop: bool, max_holds: Option<u32>) -> Player {
+        Player::new(1., BallLevel(1), BallLevel(4))
+            .with_ball_queue((1..=4).map(BallLevel))
+            .with_hold(HoldDef { slots, once_per_drop, max_holds })
+    }
+
+    #[test]
+    fn hold_swaps_with_the_ball_held() {
+        let mut rng = EntropyComponent::<ChaCha8Rng>::seed_from_u64(0);
+        let mut player = player_with_hold(1, false, None);
+        player.set_next_ball_level(&mut rng);
+
+        player.hold(&mut rng);
+        assert_eq!((player.next_ball_level, player.hold_balls.front()), (BallLevel(2), Some(&BallLevel(1))));
+        player.hold(&mut rng);
+        assert_eq!((player.next_ball_level, player.hold_balls.front()), (BallLevel(1), Some(&BallLevel(2))));
+    }
+
+    #[test]
+    fn hold_slots_are_first_in_first_out() {
+        let mut rng = EntropyComponent::<ChaCha8Rng>::seed_from_u64(0);
+        let mut player = player_with_hold(2, false, None);
+        player.set_next_ball_level(&mut rng);
+
+        player.hold(&mut rng);
+        player.hold(&mut rng);
+        assert_eq!(player.next_ball_level, BallLevel(3));
+        assert_eq!(player.hold_balls, [BallLevel(1), BallLevel(2)]);
+        player.hold(&mut rng);
+        assert_eq!(player.next_ball_level, BallLevel(1));
+        assert_eq!(player.hold_balls, [BallLevel(2), BallLevel(3)]);
+    }
+
+    #[test]
+    fn hold_is_limited_by_rules() {
+        let mut rng = EntropyComponent::<ChaCha8Rng>::seed_from_u64(0);
+
+        let mut player = player_with_hold(0, false, None);
+        player.set_next_ball_level(&mut rng);
+        assert!(!player.can_hold());
+
+        let mut player = player_with_hold(1, true, None);
+        player.set_next_ball_level(&mut rng);
+        player.hold(&mut rng);
+        assert!(!player.can_hold());
+        player.is_hold_locked = false; // dropped
+        assert!(player.can_hold());
+
+        let mut player = player_with_hold(1, false, Some(2));
+        player.set_next_ball_level(&mut rng);
+        player.hold(&mut rng);
+        player.hold(&mut rng);
+        assert_eq!(player.holds_left, Some(0));
+        assert!(!player.can_hold());
+        let before = (player.next_ball_level, player.hold_balls.clone());
+        player.hold(&mut rng);
+        assert_eq!((player.next_ball_level, player.hold_balls.clone()), before);
+    }
+}
@@ -111,12 +111,31 @@ pub fn spawn_effect(
     commands.spawn_batch(bundles);
 }
 
+/// Text rising and fading out. e.g. combo popups
+pub fn spawn_text_effect(
+    pos: Vec2,
+    commands: &mut Commands,
+    text: Text,
+) {
+    commands.spawn((
+        Effect(Timer::from_seconds(1.0, TimerMode::Once)),
+        Text2dBundle {
+            text,
+            transform: Transform::from_translation(pos.extend(Z_EFFECT)),
+            ..default()
+        },
+        EffectVelocity(Vec2::Y * 1.5),
+        EffectAlpha(effects::Linear(vec![1.0, 1.0, 0.0])),
+    ));
+}
+
 #[allow(clippy::type_complexity)]
 pub fn update_effect(
     mut commands: Commands,
     mut q_effects: Query<(
         Entity,
-        &mut Sprite,
+        Option<&mut Sprite>,
+        Option<&mut Text>,
         &mut Transform,
         &mut Effect,
         &mut EffectVelocity,
@@ -131,7 +150,7 @@ pub fn update_effect(
 ) {
     let delta = time.delta();
     let delta_second = time.delta_seconds();
-    for (entity, mut sprite, mut trans, mut effect, mut velocity, accelation, rotation, alpha, red, green, blue) in q_effects.iter_mut() {
+    for (entity, sprite, text, mut trans, mut effect, mut velocity, accelation, rotation, alpha, red, green, blue) in q_effects.iter_mut() {
         effect.0.tick(delta);
         if effect.0.finished() {
             commands.entity(entity)
@@ -171,7 +190,15 @@ pub fn update_effect(
             let cur_pos = trans.translation.xy();
             let next_pos = cur_pos + velocity.0;
 
-            sprite.color = Color::rgba(red, green, blue, alpha);
+            if let Some(mut sprite) = sprite {
+                sprite.color = Color::rgba(red, green, blue, alpha);
+            }
+            if let Some(mut text) = text {
+                // Colors of texts are kept.
+                for section in text.sections.iter_mut() {
+                    section.style.color.set_a(alpha);
+                }
+            }
 
             trans.translation.x = next_pos.x;
             trans.translation.y = next_pos.y;
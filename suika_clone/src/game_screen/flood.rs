@@ -0,0 +1,17 @@
+use bevy::prelude::*;
+
+use super::common::*;
+use super::BallSpawnEvent;
+
+/// Replaces `score_ball_events` in flood mode: a dropped ball scores 1.
+pub fn score_drops(
+    mut q_player: Query<&mut Player>,
+    mut ev_ball: EventReader<BallSpawnEvent>,
+) {
+    if let Ok(mut player) = q_player.get_single_mut() {
+        let drops = ev_ball.read()
+            .filter(|ev| matches!(ev, BallSpawnEvent::Drop(..)))
+            .count();
+        player.score += drops as u32;
+    }
+}
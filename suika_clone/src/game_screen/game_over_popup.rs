@@ -3,6 +3,7 @@ use bevy::prelude::*;
 
 use super::common::*;
 use super::camera::*;
+use super::replay::*;
 
 //
 //    +----------------+    
@@ -12,6 +13,7 @@ use super::camera::*;
 //    |  Score: XXXXXX |    
 //    |  press space.. |    
 //    |  press esc..   |    
+//    |  press x..     |    
 //    |       ver:xx   |    
 //    |       mode:xx  |    
 //    +----------------+    
@@ -23,8 +25,9 @@ const POPUP_STR_SCORE_Y: f32 = POPUP_STR_LABEL - 60. - 8.;
 const POPUP_STR_HIGH_SCORE_Y: f32 = POPUP_STR_SCORE_Y - 48. - 8.;
 const POPUP_STR_RESTART: f32 = POPUP_STR_HIGH_SCORE_Y - 24. - 32.;
 const POPUP_STR_GOTO_TITLE: f32 = POPUP_STR_RESTART - 36. - 8.;
+const POPUP_STR_REPLAY: f32 = POPUP_STR_GOTO_TITLE - 36. - 8.;
 
-const POPUP_STR_5_1_Y: f32 = POPUP_STR_GOTO_TITLE - 36. - 24.;
+const POPUP_STR_5_1_Y: f32 = POPUP_STR_REPLAY - 36. - 24.;
 
 #[derive(Component, Debug)]
 pub struct GameOverPopup;
@@ -39,16 +42,27 @@ pub struct GameOverPopupMessageDelay;
 pub fn setup_gameover_popup(
     mut commands: Commands,
     q_player: Query<&Player>,
+    q_protruded: Query<(), With<AreaProtruded>>,
     my_assets: Res<GameAssets>,
-    config: Res<Config>,
+    play_cond: Res<PlayCond>,
     scores: Res<Scores>,
+    state: Res<State<GameScreenState>>,
 ) {
     if let Ok(player) = q_player.get_single() {
-        let game_cnd = GameCond::new(config.game_ron_name.as_str());
-        let highscore = scores.get_highest(&game_cnd);
-        let score = Score::new(player.score);
+        let game_cnd = &play_cond.0;
+        let label = if *state.get() == GameScreenState::Cleared {
+            "CLEAR"
+        } else if q_protruded.is_empty() && game_cnd.game_mode == GameMode::TimeAttack {
+            "TIME UP"
+        } else if q_protruded.is_empty() && game_cnd.game_mode == GameMode::Puzzle {
+            "OUT OF BALLS"
+        } else {
+            "GAME OVER"
+        };
+        let highscore = scores.get_highest(game_cnd);
+        let score = player.score;
         let score_is_highest = if let Some(highscore) = highscore {
-            score > *highscore
+            score > highscore.score
         } else {
             true
         };
@@ -57,9 +71,9 @@ pub fn setup_gameover_popup(
         let high_score_txt = format!("high score: {}", highscore);
 
         let score_txt = if score_is_highest {
-            format!("New High Score:{:>6}", score.score)
+            format!("New High Score:{:>6}", score)
         } else {
-            format!("Score:{:>6}", score.score)
+            format!("Score:{:>6}", score)
         };
 
         commands.spawn((
@@ -92,7 +106,7 @@ pub fn setup_gameover_popup(
             };
             b.spawn((
                 Text2dBundle {
-                    text: Text::from_section("GAME OVER", text_style),
+                    text: Text::from_section(label, text_style),
                     transform: Transform::from_translation(
                         Vec2::new(0., POPUP_STR_LABEL).extend(Z_POPUP + 0.01)
                     ),
@@ -168,6 +182,24 @@ pub fn setup_gameover_popup(
                     ..default()
                 },
             ));
+            let text_style = TextStyle {
+                font: my_assets.h_font.clone(),
+                font_size: 36.0,
+                color: my_assets.ui.popup.font_color_sub,
+            };
+            b.spawn((
+                GameOverPopupMessageDelay,
+                Text2dBundle {
+                    text: Text::from_section(
+                        format!("Press [{}] to watch the replay.", GpKbInput::Sub1.get_str()), text_style),
+                    transform: Transform::from_translation(
+                        Vec2::new(0., POPUP_STR_REPLAY).extend(Z_POPUP + 0.01)
+                    ),
+                    visibility: Visibility::Hidden,
+                    text_anchor: bevy::sprite::Anchor::Center,
+                    ..default()
+                },
+            ));
             let text_style = TextStyle {
                 font: my_assets.h_font.clone(),
                 font_size: 24.0,
@@ -176,7 +208,7 @@ pub fn setup_gameover_popup(
             b.spawn((
                 Text2dBundle {
                     text: Text::from_section(
-                        format!("v{}, mode:{}", game_cnd.app_ver, game_cnd.mode), text_style),
+                        format!("v{}, {}, mode:{}", game_cnd.app_ver, game_cnd.game_ron_name, game_cnd.get_mode_str()), text_style),
                     transform: Transform::from_translation(
                         Vec2::new(0., POPUP_STR_5_1_Y).extend(Z_POPUP + 0.01)
                     ),
@@ -280,6 +312,7 @@ pub fn update_gameover_popup(
 pub enum GameOverPopupInput {
     Restart,
     GoToTitle,
+    WatchReplay,
 }
 
 pub fn read_keyboard_for_gameover_popup(
@@ -292,6 +325,9 @@ pub fn read_keyboard_for_gameover_popup(
     if keyboard.any_just_pressed(KEYBOARD_KEYS_SELECT) {
         ev_input.send(GameOverPopupInput::GoToTitle);
     }
+    if keyboard.any_just_pressed(KEYBOARD_KEYS_SUB1) {
+        ev_input.send(GameOverPopupInput::WatchReplay);
+    }
 }
 
 pub fn read_gamepad_for_gameover_popup(
@@ -307,24 +343,38 @@ pub fn read_gamepad_for_gameover_popup(
         if buttons.any_just_pressed(to_gamepad_btn(gamepad, &GAMEPAD_BTNS_SELECT)) {
             ev_input.send(GameOverPopupInput::GoToTitle);
         }
+        if buttons.any_just_pressed(to_gamepad_btn(gamepad, &GAMEPAD_BTNS_SUB1)) {
+            ev_input.send(GameOverPopupInput::WatchReplay);
+        }
     }
 }
 
 pub fn act_gameover_popup(
     mut next_screen_state: ResMut<NextState<GameScreenState>>,
     mut next_state: ResMut<NextState<GameState>>,
+    mut replay_mode: ResMut<ReplayMode>,
     q_controller: Query<&ControllerGameOverPopup>,
     mut ev_input: EventReader<GameOverPopupInput>,
+    last_replay: Res<LastReplay>,
 ) {
     if let Ok(controller) = q_controller.get_single() {
         for ev in ev_input.read() {
             match ev {
                 GameOverPopupInput::Restart => {
                     if controller.input_suppresser.finished() {
+                        *replay_mode = ReplayMode::Record;
                         next_screen_state.set(GameScreenState::Init);
                         // FIXME: Should return?
                     }
                 },
+                GameOverPopupInput::WatchReplay => {
+                    if controller.input_suppresser.finished() {
+                        if let Some(replay) = last_replay.0.as_ref() {
+                            *replay_mode = ReplayMode::Play(replay.clone());
+                            next_screen_state.set(GameScreenState::Init);
+                        }
+                    }
+                },
                 GameOverPopupInput::GoToTitle => {
                     if controller.input_suppresser.finished() {
                         next_state.set(GameState::Title);
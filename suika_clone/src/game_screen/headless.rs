@@ -0,0 +1,182 @@
+//! A harness that runs `ScGameSimPlugin` without a window, a renderer or assets.
+//! Inputs are given as a `Replay`, and the game advances a fixed timestep per step.
+use std::time::Duration;
+
+use super::*;
+use bevy::time::TimeUpdateStrategy;
+use game_ron::{GameRon, PuzzleRon};
+
+/// Gives default handles. Nothing is loaded.
+pub struct NoLoading;
+
+impl HandleLoader for NoLoading {
+    fn load<A: Asset>(&self, _path: &str) -> Handle<A> {
+        Handle::default()
+    }
+}
+
+pub fn kao_game_ron() -> GameRon {
+    ron::from_str(include_str!("../../assets/ron/kao.game.ron"))
+        .expect("kao.game.ron is invalid")
+}
+
+pub struct HeadlessGame {
+    app: App,
+}
+
+impl HeadlessGame {
+    pub fn new(game_ron: &GameRon, replay: Replay) -> Self {
+        Self::start(game_ron, replay, None)
+    }
+
+    /// The replay should be in the puzzle mode.
+    pub fn new_puzzle(game_ron: &GameRon, puzzle: PuzzleRon, replay: Replay) -> Self {
+        Self::start(game_ron, replay, Some(puzzle))
+    }
+
+    fn start(game_ron: &GameRon, replay: Replay, puzzle: Option<PuzzleRon>) -> Self {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            TransformPlugin,
+            HierarchyPlugin,
+            EntropyPlugin::<ChaCha8Rng>::default(),
+            PhysicsPlugins::new(FixedUpdate)
+                .build()
+                .disable::<PhysicsDebugPlugin>()
+                .add(LimitVelocityPlugin),
+            ScGameSimPlugin,
+        ));
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(
+            Duration::from_secs_f64(1. / FIXED_TIMESTEP_HZ)));
+        app.insert_resource(GameAssets::create_with_loading(game_ron, &NoLoading));
+        app.insert_resource(Config::default());
+        app.insert_resource(ReplayMode::Play(replay));
+        if let Some(puzzle) = puzzle {
+            app.insert_resource(Puzzle(puzzle));
+        }
+
+        app.world.resource_mut::<NextState<GameScreenState>>()
+            .set(GameScreenState::Init);
+        app.update(); // Init
+        app.update(); // -> Playing
+
+        Self { app }
+    }
+
+    /// Advances the game by `frames` fixed timesteps or until the game is over or cleared.
+    pub fn step(&mut self, frames: u64) {
+        let target = self.frame() + frames;
+        // A fixed timestep can be skipped or doubled by rounding errors of the clock.
+        let max_updates = frames * 2 + 10;
+        for _ in 0..max_updates {
+            if self.frame() >= target || self.is_game_over() || self.is_cleared() {
+                break;
+            }
+            self.app.update();
+        }
+    }
+
+    pub fn frame(&self) -> u64 {
+        self.app.world.resource::<PlayFrame>().0
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        *self.app.world.resource::<State<GameScreenState>>().get() == GameScreenState::GameOver
+    }
+
+    pub fn is_cleared(&self) -> bool {
+        *self.app.world.resource::<State<GameScreenState>>().get() == GameScreenState::Cleared
+    }
+
+    pub fn score(&mut self) -> u32 {
+        self.app.world.query::<&Player>()
+            .single(&self.app.world)
+            .score
+    }
+
+    /// Levels and positions of all balls.
+    pub fn balls(&mut self) -> Vec<(BallLevel, Vec2)> {
+        self.app.world.query::<(&Ball, &Transform)>()
+            .iter(&self.app.world)
+            .map(|(b, t)| (b.level, t.translation.xy()))
+            .collect()
+    }
+
+    /// Position and angle of the bottle.
+    pub fn bottle_pose(&mut self) -> (Vec2, f32) {
+        let transform = self.app.world.query_filtered::<&Transform, With<Bottle>>()
+            .single(&self.app.world);
+        (transform.translation.xy(), transform.rotation.to_euler(EulerRot::XYZ).2)
+    }
+
+    pub fn player(&mut self) -> &Player {
+        self.app.world.query::<&Player>()
+            .single(&self.app.world)
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        &mut self.app.world
+    }
+}
+
+/// Moves the player to `x`, drops a ball and waits `wait_frames`.
+#[derive(Debug, Clone, Copy)]
+pub struct ScriptedDrop {
+    pub x: f32,
+    pub wait_frames: u64,
+}
+
+/// Creates a replay which performs `drops` in order.
+pub fn replay_from_drops(game_ron: &GameRon, seed: u64, drops: &[ScriptedDrop]) -> Replay {
+    let speed = game_ron.player.speed;
+    let mut inputs = vec![];
+    let mut frame = 1;
+    let mut x = 0.;
+    for drop in drops {
+        let dx: f32 = drop.x - x;
+        let steps = dx.abs() / speed;
+        let lrs = std::iter::repeat(dx.signum()).take(steps.floor() as usize)
+            .chain((steps.fract() > 0.).then_some(dx.signum() * steps.fract()));
+        for lr in lrs {
+            inputs.push((frame, PlayerInputEvent::Move(lr)));
+            frame += 1;
+        }
+        x = drop.x;
+        inputs.push((frame, PlayerInputEvent::Drop));
+        frame += 1 + drop.wait_frames;
+    }
+
+    Replay {
+        seed,
+        inputs,
+        ..default()
+    }
+}
+
+#[test]
+fn dropped_balls_stay_in_bottle() {
+    let game_ron = kao_game_ron();
+    let drops = [-100., 0., 100.].map(|x| ScriptedDrop { x, wait_frames: 60 });
+    let mut game = HeadlessGame::new(&game_ron, replay_from_drops(&game_ron, 0, &drops));
+
+    game.step(300);
+
+    assert!(!game.is_game_over());
+    assert!(game.balls().len() >= 2);
+}
+
+#[test]
+fn same_replay_gives_same_result() {
+    let game_ron = kao_game_ron();
+    let drops = [0., 30., -30., 10., -10., 0.].map(|x| ScriptedDrop { x, wait_frames: 40 });
+    let replay = replay_from_drops(&game_ron, 42, &drops);
+
+    let mut a = HeadlessGame::new(&game_ron, replay.clone());
+    let mut b = HeadlessGame::new(&game_ron, replay);
+    a.step(600);
+    b.step(600);
+
+    assert_eq!(a.score(), b.score());
+    assert_eq!(a.balls(), b.balls());
+}
@@ -0,0 +1,201 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use game_ron::MissionRon;
+
+use super::common::*;
+use super::puzzle::Puzzle;
+use super::replay::*;
+use super::BallSpawnEvent;
+
+/// Missions of the current game.
+/// They are the game.ron's in the mission mode, and the puzzle's goal in the puzzle mode.
+#[derive(Resource, Debug, Default)]
+pub struct ActiveMissions(pub Vec<MissionRon>);
+
+pub fn has_missions(
+    game_mode: Res<PlayGameMode>,
+) -> bool {
+    matches!(game_mode.0, GameMode::Mission | GameMode::Puzzle)
+}
+
+/// Progress of missions which can't be seen from balls and the player.
+#[derive(Resource, Debug, Default)]
+pub struct MissionProgress {
+    pub max_level_made: usize,
+    pub combines: u32,
+}
+
+/// Current values compared with missions.
+#[derive(Debug, Clone, Copy)]
+pub struct MissionStatus<'a> {
+    pub progress: &'a MissionProgress,
+    pub score: u32,
+    pub balls: usize,
+}
+
+impl MissionStatus<'_> {
+    pub fn is_met(&self, mission: &MissionRon) -> bool {
+        match *mission {
+            MissionRon::MakeBall(level) => self.progress.max_level_made >= level,
+            MissionRon::Score(score) => self.score >= score,
+            MissionRon::Combine(n) => self.progress.combines >= n,
+            MissionRon::FewerBallsThan(n) => self.balls < n,
+        }
+    }
+
+    pub fn is_all_met(&self, missions: &[MissionRon]) -> bool {
+        // Without missions, the game is never cleared.
+        !missions.is_empty() && missions.iter().all(|m| self.is_met(m))
+    }
+
+    fn describe(&self, mission: &MissionRon) -> String {
+        let check = if self.is_met(mission) { "[x]" } else { "[ ]" };
+        let desc = match *mission {
+            MissionRon::MakeBall(level) => format!("Lv.{} ball", level),
+            MissionRon::Score(score) => format!("{} pts", score),
+            MissionRon::Combine(n) => format!("{}/{} combos", self.progress.combines.min(n), n),
+            MissionRon::FewerBallsThan(n) => format!("<{} balls ({})", n, self.balls),
+        };
+        format!("{} {}\n", check, desc)
+    }
+}
+
+pub fn setup_missions(
+    mut progress: ResMut<MissionProgress>,
+    mut missions: ResMut<ActiveMissions>,
+    game_mode: Res<PlayGameMode>,
+    puzzle: Option<Res<Puzzle>>,
+    assets: Res<GameAssets>,
+) {
+    *progress = default();
+    missions.0 = match (game_mode.0, puzzle) {
+        (GameMode::Mission, _) => assets.missions.clone(),
+        (GameMode::Puzzle, Some(puzzle)) => puzzle.0.goal.clone(),
+        _ => vec![],
+    };
+}
+
+pub fn track_mission_progress(
+    mut progress: ResMut<MissionProgress>,
+    mut ev_ball: EventReader<BallSpawnEvent>,
+) {
+    for ev in ev_ball.read() {
+        if let BallSpawnEvent::Combine(_, level) = ev {
+            progress.combines += 1;
+            if let Some(level) = level {
+                progress.max_level_made = progress.max_level_made.max(level.0);
+            }
+        }
+    }
+}
+
+pub fn check_missions_cleared(
+    progress: Res<MissionProgress>,
+    q_player: Query<&Player>,
+    q_balls: Query<(), With<Ball>>,
+    missions: Res<ActiveMissions>,
+    mut next_state: ResMut<NextState<GameScreenState>>,
+) {
+    if let Ok(player) = q_player.get_single() {
+        let status = MissionStatus {
+            progress: &progress,
+            score: player.score,
+            balls: q_balls.iter().count(),
+        };
+        if status.is_all_met(&missions.0) {
+            info!("Cleared: {:?}", status);
+            next_state.set(GameScreenState::Cleared);
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct MissionView;
+
+#[derive(Component, Debug)]
+pub struct MissionText;
+
+pub fn spawn_mission_view(
+    mut commands: Commands,
+    missions: Res<ActiveMissions>,
+    my_assets: Res<GameAssets>,
+) {
+    let border_width = my_assets.ui.hold_view.border_width;
+    let inner_margin = 4.;
+    let label_weight = super::FONT_WEIGHT_M;
+    let size = my_assets.mission_view_size();
+    commands
+        .spawn((
+            MissionView,
+            SpriteBundle { // as frame
+                texture: my_assets.ui.hold_view.h_bg_image.clone(),
+                sprite: Sprite {
+                    custom_size: Some(size),
+                    ..default()
+                },
+                transform: Transform::from_translation(
+                               my_assets.mission_view_center().extend(Z_UI)),
+                ..default()
+            },
+            ImageScaleMode::Sliced(TextureSlicer {
+                border: BorderRect::square(border_width),
+                center_scale_mode: SliceScaleMode::Tile { stretch_value: 1.0 },
+                sides_scale_mode: SliceScaleMode::Tile { stretch_value: 1.0 },
+                ..default()
+            }),
+        ))
+        .with_children(|b| {
+            let top = size.y/2. - border_width - inner_margin;
+            let left = -size.x/2. + border_width;
+            let text_style = TextStyle {
+                font: my_assets.h_font.clone(),
+                font_size: label_weight,
+                color: my_assets.ui.hold_view.font_color,
+            };
+            b.spawn((
+                Text2dBundle {
+                    text: Text::from_section("Mission", text_style),
+                    transform: Transform::from_translation(
+                        Vec2::new(0., top - label_weight/2.).extend(0.01)),
+                    ..default()
+                },
+            ));
+
+            let text_style = TextStyle {
+                font: my_assets.h_font.clone(),
+                font_size: super::FONT_WEIGHT_S,
+                color: my_assets.ui.hold_view.font_color,
+            };
+            let sections = missions.0.iter()
+                .map(|_| TextSection::new("", text_style.clone()));
+            b.spawn((
+                MissionText,
+                Text2dBundle {
+                    text: Text::from_sections(sections),
+                    transform: Transform::from_translation(
+                        Vec2::new(left, top - label_weight - inner_margin).extend(0.01)),
+                    text_anchor: bevy::sprite::Anchor::TopLeft,
+                    ..default()
+                },
+            ));
+        });
+}
+
+pub fn update_mission_view(
+    mut q_text: Query<&mut Text, With<MissionText>>,
+    progress: Res<MissionProgress>,
+    q_player: Query<&Player>,
+    q_balls: Query<(), With<Ball>>,
+    missions: Res<ActiveMissions>,
+) {
+    if let (Ok(mut text), Ok(player)) = (q_text.get_single_mut(), q_player.get_single()) {
+        let status = MissionStatus {
+            progress: &progress,
+            score: player.score,
+            balls: q_balls.iter().count(),
+        };
+        for (section, mission) in text.sections.iter_mut().zip(missions.0.iter()) {
+            section.value = status.describe(mission);
+        }
+    }
+}
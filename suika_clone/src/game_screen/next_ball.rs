@@ -0,0 +1,187 @@
+use crate::prelude::*;
+use rand_core::RngCore;
+
+/// Picks levels of next balls by `NextBallDef`.
+#[derive(Debug, Clone)]
+pub struct NextBallPicker {
+    def: NextBallDef,
+    /// Balls left in the bag. The last one is picked first.
+    bag: Vec<BallLevel>,
+    /// The last level picked and how many times in a row.
+    last: Option<(BallLevel, u32)>,
+}
+
+impl Default for NextBallPicker {
+    fn default() -> Self {
+        Self::new(NextBallDef::uniform(default()))
+    }
+}
+
+impl NextBallPicker {
+    pub fn new(def: NextBallDef) -> Self {
+        Self {
+            def,
+            bag: vec![],
+            last: None,
+        }
+    }
+
+    pub fn pick(&mut self, rng: &mut impl RngCore) -> BallLevel {
+        let banned = self.banned();
+        let level = if self.def.bag {
+            self.pick_from_bag(rng, banned)
+        } else {
+            self.pick_weighted(rng.next_u32(), banned)
+        };
+        self.last = match self.last {
+            Some((last, n)) if last == level => Some((last, n + 1)),
+            _ => Some((level, 1)),
+        };
+        level
+    }
+
+    /// The level which can't be picked by `max_repeat`.
+    fn banned(&self) -> Option<BallLevel> {
+        match (self.last, self.def.max_repeat) {
+            (Some((level, n)), Some(max_repeat)) if n >= max_repeat => Some(level),
+            _ => None,
+        }
+    }
+
+    fn weight(&self, level: BallLevel, banned: Option<BallLevel>) -> u64 {
+        if Some(level) == banned {
+            0
+        } else {
+            self.def.weights[level.0 - BALL_LEVEL_MIN] as u64
+        }
+    }
+
+    fn levels(&self) -> impl Iterator<Item = BallLevel> {
+        (0..self.def.weights.len()).map(|i| BallLevel::new(i + BALL_LEVEL_MIN))
+    }
+
+    /// Uniform weights give the same level as `rnd % (number of levels)`.
+    pub(super) fn pick_weighted(&self, rnd: u32, banned: Option<BallLevel>) -> BallLevel {
+        let total: u64 = self.levels().map(|lv| self.weight(lv, banned)).sum();
+        if total == 0 {
+            // Only the banned level can be picked.
+            return match banned {
+                Some(_) => self.pick_weighted(rnd, None),
+                None => BallLevel::new(BALL_LEVEL_MIN),
+            };
+        }
+        let mut r = rnd as u64 % total;
+        for level in self.levels() {
+            let weight = self.weight(level, banned);
+            if r < weight {
+                return level;
+            }
+            r -= weight;
+        }
+        unreachable!("r is less than the total of weights")
+    }
+
+    fn pick_from_bag(&mut self, rng: &mut impl RngCore, banned: Option<BallLevel>) -> BallLevel {
+        if self.bag.is_empty() {
+            self.add_bag(rng);
+        }
+        // `max_repeat` is ignored if only the banned level is left.
+        let idx = self.bag.iter().rposition(|lv| Some(*lv) != banned)
+            .unwrap_or(self.bag.len() - 1);
+        self.bag.remove(idx)
+    }
+
+    fn add_bag(&mut self, rng: &mut impl RngCore) {
+        let mut bag: Vec<_> = self.levels()
+            .flat_map(|lv| std::iter::repeat(lv).take(self.def.weights[lv.0 - BALL_LEVEL_MIN] as usize))
+            .collect();
+        if bag.is_empty() {
+            bag.push(BallLevel::new(BALL_LEVEL_MIN));
+        }
+        // Fisher-Yates
+        for i in (1..bag.len()).rev() {
+            let j = rng.next_u32() as usize % (i + 1);
+            bag.swap(i, j);
+        }
+        self.bag = bag;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_prng::ChaCha8Rng;
+    use bevy_rand::prelude::*;
+    use itertools::Itertools;
+    use rand_core::SeedableRng;
+
+    fn next_ball_def(weights: &[u32], bag: bool, max_repeat: Option<u32>) -> NextBallDef {
+        NextBallDef {
+            weights: weights.to_vec(),
+            bag,
+            max_repeat,
+        }
+    }
+
+    #[test]
+    fn rand_level_is_in_range() {
+        // Level 1 has no weight so that the range doesn't start from `BALL_LEVEL_MIN`.
+        let picker = NextBallPicker::new(next_ball_def(&[0, 1, 1, 1, 1], false, None));
+        let (min, max) = (BallLevel(2), BallLevel(5));
+        for rnd in [0, 1, 2, 3, 4, 5, u32::MAX - 1, u32::MAX] {
+            let level = picker.pick_weighted(rnd, None);
+            assert!((min..=max).contains(&level), "{} -> {:?}", rnd, level);
+        }
+        assert_eq!(picker.pick_weighted(0, None), min);
+        assert_eq!(picker.pick_weighted(3, None), max);
+        let picker = NextBallPicker::new(next_ball_def(&[0, 1], false, None));
+        assert_eq!(picker.pick_weighted(u32::MAX, None), min);
+    }
+
+    #[test]
+    fn picked_level_is_in_range_in_every_mode() {
+        let max = BallLevel(5);
+        let (min, weights) = (BallLevel(BALL_LEVEL_MIN), NextBallDef::uniform(max).weights);
+        for (bag, max_repeat) in [(false, None), (true, None), (false, Some(1)), (true, Some(1))] {
+            let mut rng = EntropyComponent::<ChaCha8Rng>::seed_from_u64(0);
+            let mut picker = NextBallPicker::new(next_ball_def(&weights, bag, max_repeat));
+            // Refills the bag many times.
+            for _ in 0..100 {
+                let level = picker.pick(&mut rng);
+                assert!((min..=max).contains(&level), "bag: {}, max_repeat: {:?} -> {:?}", bag, max_repeat, level);
+            }
+        }
+    }
+
+    #[test]
+    fn weighted_level_follows_weights() {
+        let picker = NextBallPicker::new(next_ball_def(&[2, 0, 1], false, None));
+        let levels = (0..6).map(|rnd| picker.pick_weighted(rnd, None).0).collect_vec();
+        assert_eq!(levels, [1, 1, 3, 1, 1, 3]);
+        assert_eq!(picker.pick_weighted(2, Some(BallLevel(3))), BallLevel(1));
+    }
+
+    #[test]
+    fn bag_has_all_balls_of_weights() {
+        let mut rng = EntropyComponent::<ChaCha8Rng>::seed_from_u64(0);
+        let mut picker = NextBallPicker::new(next_ball_def(&[3, 2, 1], true, None));
+        for _ in 0..3 {
+            let levels = (0..6).map(|_| picker.pick(&mut rng).0).sorted().collect_vec();
+            assert_eq!(levels, [1, 1, 1, 2, 2, 3]);
+        }
+    }
+
+    #[test]
+    fn same_level_is_not_repeated_more_than_max_repeat() {
+        let mut rng = EntropyComponent::<ChaCha8Rng>::seed_from_u64(0);
+        let mut picker = NextBallPicker::new(next_ball_def(&[20, 1], false, Some(2)));
+        let levels = (0..100).map(|_| picker.pick(&mut rng)).collect_vec();
+        assert!(levels.windows(3).all(|w| !(w[0] == w[1] && w[1] == w[2])), "{:?}", levels);
+
+        // Also across bags.
+        let mut picker = NextBallPicker::new(next_ball_def(&[1, 1, 1], true, Some(1)));
+        let levels = (0..100).map(|_| picker.pick(&mut rng)).collect_vec();
+        assert!(levels.windows(2).all(|w| w[0] != w[1]), "{:?}", levels);
+    }
+}
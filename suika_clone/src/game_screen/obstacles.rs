@@ -0,0 +1,134 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use bevy_xpbd_2d::prelude::*;
+use game_ron::ObstacleShapeRon;
+
+use super::replay::PlayFrame;
+use super::{Bottle, FIXED_TIMESTEP_HZ};
+
+pub fn has_obstacles(
+    my_assets: Res<GameAssets>,
+) -> bool {
+    !my_assets.obstacles.is_empty()
+}
+
+/// Index of `GameAssets::obstacles`.
+#[derive(Component, Debug)]
+pub struct Obstacle(pub usize);
+
+/// An obstacle with `angular_velocity`.
+/// It's a kinematic body of its own, following the bottle by its velocity.
+#[derive(Component, Debug)]
+pub struct RotatingObstacle;
+
+fn create_collider(shape: &ObstacleShapeRon) -> Collider {
+    match *shape {
+        ObstacleShapeRon::Circle { radius } => Collider::circle(radius),
+        ObstacleShapeRon::Rectangle { width, height } => Collider::rectangle(width, height),
+        ObstacleShapeRon::Capsule { length, radius } => Collider::capsule_endpoints(
+            Vec2::new(-length / 2., 0.), Vec2::new(length / 2., 0.), radius),
+    }
+}
+
+/// Spawns obstacles as children of the bottle to move with it.
+/// Rotating ones are spawned at the same place but apart from the bottle. See `move_rotating_obstacles`.
+pub fn spawn_obstacles(
+    mut commands: Commands,
+    q_bottle: Query<(Entity, &Bottle)>,
+    my_assets: Res<GameAssets>,
+) {
+    let Ok((entity, bottle)) = q_bottle.get_single() else {
+        return;
+    };
+    let bottom_c = my_assets.bottle_settings.inner_bottom_center() - bottle.origin;
+    for (i, obstacle) in my_assets.obstacles.iter().enumerate() {
+        let local_pos = bottom_c + obstacle.position;
+        let components = (
+            Obstacle(i),
+            create_collider(&obstacle.shape),
+            Restitution {
+                coefficient: obstacle.physics.restitution.coef,
+                ..default()
+            },
+            Friction {
+                dynamic_coefficient: obstacle.physics.friction.dynamic_coef,
+                static_coefficient: obstacle.physics.friction.static_coef,
+                ..default()
+            },
+        );
+        if obstacle.angular_velocity == 0. {
+            commands.entity(entity).with_children(|b| {
+                b.spawn((
+                    components,
+                    SpatialBundle {
+                        transform: Transform::from_translation(local_pos.extend(0.01))
+                            .with_rotation(Quat::from_rotation_z(obstacle.rotation)),
+                        ..default()
+                    },
+                ));
+            });
+        } else {
+            commands.spawn((
+                components,
+                RotatingObstacle,
+                RigidBody::Kinematic,
+                LinearVelocity::ZERO,
+                AngularVelocity(obstacle.angular_velocity),
+                SpatialBundle {
+                    transform: Transform::from_translation((bottle.origin + local_pos).extend(Z_WALL + 0.01))
+                        .with_rotation(Quat::from_rotation_z(obstacle.rotation)),
+                    ..default()
+                },
+            ));
+        }
+    }
+}
+
+/// Moves rotating obstacles by their velocity to the pose on the bottle in the next step,
+/// so that they push balls consistently like the bottle.
+pub fn move_rotating_obstacles(
+    mut q_obstacles: Query<(&Obstacle, &Transform, &mut LinearVelocity, &mut AngularVelocity), With<RotatingObstacle>>,
+    q_bottle: Query<(&Bottle, &Transform, &LinearVelocity, &AngularVelocity), Without<Obstacle>>,
+    play_frame: Res<PlayFrame>,
+    time: Res<Time>,
+    my_assets: Res<GameAssets>,
+) {
+    let Ok((bottle, bottle_trans, bottle_velocity, bottle_angular_velocity)) = q_bottle.get_single() else {
+        return;
+    };
+    let delta_sec = time.delta_seconds();
+    if delta_sec <= 0. {
+        return;
+    }
+    let bottle_pos = bottle_trans.translation.xy() + bottle_velocity.0 * delta_sec;
+    let bottle_angle = bottle_trans.rotation.to_euler(EulerRot::XYZ).2 + bottle_angular_velocity.0 * delta_sec;
+    let bottom_c = my_assets.bottle_settings.inner_bottom_center() - bottle.origin;
+    let sec = (play_frame.0 as f64 / FIXED_TIMESTEP_HZ) as f32;
+    for (Obstacle(i), transform, mut velocity, mut angular_velocity) in q_obstacles.iter_mut() {
+        let obstacle = &my_assets.obstacles[*i];
+        let target_pos = bottle_pos + Vec2::from_angle(bottle_angle).rotate(bottom_c + obstacle.position);
+        let target_angle = bottle_angle + obstacle.rotation + obstacle.angular_velocity * sec;
+        let angle = transform.rotation.to_euler(EulerRot::XYZ).2;
+        // The shortest way in (-PI, PI].
+        let d_angle = Vec2::from_angle(angle).angle_between(Vec2::from_angle(target_angle));
+        velocity.0 = (target_pos - transform.translation.xy()) / delta_sec;
+        angular_velocity.0 = d_angle / delta_sec;
+    }
+}
+
+pub fn spawn_obstacle_views(
+    mut commands: Commands,
+    q_obstacles: Query<(Entity, &Obstacle)>,
+    my_assets: Res<GameAssets>,
+) {
+    for (entity, Obstacle(i)) in q_obstacles.iter() {
+        let obstacle = &my_assets.obstacles[*i];
+        commands.entity(entity).insert((
+            Sprite {
+                custom_size: Some(obstacle.shape.size()),
+                ..default()
+            },
+            obstacle.h_image.clone(),
+        ));
+    }
+}
@@ -0,0 +1,135 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use bevy_xpbd_2d::prelude::*;
+
+use super::common::*;
+use super::replay::*;
+use super::FIXED_TIMESTEP_HZ;
+
+/// Balls slower than this are at rest.
+const REST_SPEED: f32 = 10.;
+
+/// A ball to check by `is_overflowing`.
+#[derive(Debug, Clone, Copy)]
+pub struct BallStatus {
+    pub pos: Vec2,
+    pub is_dropping: bool,
+    pub is_growing: bool,
+    /// Seconds since the ball came to rest. 0 if moving.
+    pub rest_sec: f32,
+}
+
+/// Whether the ball is out of `area` and counts by `def`.
+/// The game is over if it lasts `game_over_grace_sec`.
+pub fn is_overflowing(ball: &BallStatus, area: &game_ron::Area, def: &OverflowDef) -> bool {
+    let &game_ron::Area { min_x, max_x, min_y, max_y } = area;
+    // Balls escaped from the bottle keep falling, so they can't settle.
+    let is_escaped = !(min_x..=max_x).contains(&ball.pos.x) || ball.pos.y < min_y;
+    let is_over = ball.pos.y > max_y;
+    let is_settled = is_escaped || ball.rest_sec >= def.settle_sec;
+    (is_escaped || is_over)
+        && !(def.ignore_dropping && ball.is_dropping)
+        && !(def.ignore_growing && ball.is_growing)
+        && is_settled
+}
+
+pub fn needs_settle(
+    my_assets: Res<GameAssets>,
+) -> bool {
+    my_assets.physics.overflow.settle_sec > 0.
+}
+
+/// The frame when the ball came to rest.
+#[derive(Component, Debug)]
+pub struct RestingSince(pub u64);
+
+impl RestingSince {
+    pub fn rest_sec(since: Option<&Self>, frame: u64) -> f32 {
+        since.map_or(0., |s| (frame - s.0) as f64 / FIXED_TIMESTEP_HZ) as f32
+    }
+}
+
+pub fn track_resting_balls(
+    mut commands: Commands,
+    q_balls: Query<(Entity, Option<&LinearVelocity>, Option<&RestingSince>), With<Ball>>,
+    play_frame: Res<PlayFrame>,
+) {
+    for (entity, velocity, since) in q_balls.iter() {
+        let is_resting = velocity.map_or(true, |v| v.0.length() < REST_SPEED);
+        match (is_resting, since) {
+            (true, None) => {
+                commands.entity(entity)
+                    .insert(RestingSince(play_frame.0));
+            },
+            (false, Some(_)) => {
+                commands.entity(entity)
+                    .remove::<RestingSince>();
+            },
+            _ => {},
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ball_status_out_of(area: &game_ron::Area) -> BallStatus {
+        BallStatus {
+            pos: Vec2::new(area.max_x + 1., area.min_y),
+            is_dropping: false,
+            is_growing: false,
+            rest_sec: 0.,
+        }
+    }
+
+    #[test]
+    fn overflow_ignores_balls_by_rules() {
+        let area = game_ron::OtherParamRon::default().area;
+        let all = OverflowDef::default();
+        let rules = OverflowDef {
+            ignore_dropping: true,
+            ignore_growing: true,
+            settle_sec: 0.,
+        };
+        let inside = BallStatus { pos: Vec2::new(area.min_x, area.max_y), ..ball_status_out_of(&area) };
+        let dropping = BallStatus { is_dropping: true, ..ball_status_out_of(&area) };
+        let growing = BallStatus { is_growing: true, ..ball_status_out_of(&area) };
+
+        assert!(!is_overflowing(&inside, &area, &all));
+        assert!(is_overflowing(&dropping, &area, &all));
+        assert!(is_overflowing(&growing, &area, &all));
+        assert!(!is_overflowing(&dropping, &area, &rules));
+        assert!(!is_overflowing(&growing, &area, &rules));
+        assert!(is_overflowing(&ball_status_out_of(&area), &area, &rules));
+    }
+
+    #[test]
+    fn overflow_waits_for_balls_to_settle() {
+        let area = game_ron::OtherParamRon::default().area;
+        let def = OverflowDef {
+            settle_sec: 0.5,
+            ..default()
+        };
+        let moving = BallStatus { pos: Vec2::new(0., area.max_y + 1.), ..ball_status_out_of(&area) };
+        let resting = BallStatus { rest_sec: 0.5, ..moving };
+
+        assert!(!is_overflowing(&moving, &area, &def));
+        assert!(is_overflowing(&resting, &area, &def));
+    }
+
+    #[test]
+    fn overflow_does_not_wait_for_escaped_balls() {
+        let area = game_ron::OtherParamRon::default().area;
+        let def = OverflowDef {
+            settle_sec: 0.5,
+            ..default()
+        };
+        let beside = ball_status_out_of(&area);
+        let below = BallStatus { pos: Vec2::new(0., area.min_y - 1.), ..beside };
+
+        assert!(is_overflowing(&beside, &area, &def));
+        assert!(is_overflowing(&below, &area, &def));
+    }
+}
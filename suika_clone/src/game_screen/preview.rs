@@ -0,0 +1,133 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+
+use super::common::*;
+
+pub fn has_preview(
+    my_assets: Res<GameAssets>,
+) -> bool {
+    my_assets.ui.preview_view.is_some()
+}
+
+#[derive(Component, Debug)]
+pub struct PreviewView;
+
+/// (index of upcoming balls, level shown)
+#[derive(Component, Debug)]
+pub struct PreviewBallImage(usize, Option<BallLevel>);
+
+const INNER_MARGIN: f32 = 4.;
+const LABEL_WEIGHT: f32 = super::FONT_WEIGHT_M;
+
+/// Size of the space for a ball.
+fn slot_size(preview: &PreviewViewDef) -> Vec2 {
+    Vec2::new(
+        preview.width - preview.border_width * 2.,
+        (preview.height - preview.border_width * 2. - LABEL_WEIGHT - INNER_MARGIN * 2.)
+            / preview.count.max(1) as f32,
+    )
+}
+
+pub fn spawn_preview_view(
+    mut commands: Commands,
+    my_assets: Res<GameAssets>,
+) {
+    let Some(preview) = my_assets.ui.preview_view.as_ref() else {
+        return;
+    };
+    let border_width = preview.border_width;
+    let inner_margin = INNER_MARGIN;
+    let label_weight = LABEL_WEIGHT;
+    let size = my_assets.preview_view_size();
+    let slot_size = slot_size(preview);
+    commands
+        .spawn((
+            PreviewView,
+            SpriteBundle { // as frame
+                texture: preview.h_bg_image.clone(),
+                sprite: Sprite {
+                    custom_size: Some(size),
+                    ..default()
+                },
+                transform: Transform::from_translation(
+                               my_assets.preview_view_center().extend(Z_UI)),
+                ..default()
+            },
+            ImageScaleMode::Sliced(TextureSlicer {
+                border: BorderRect::square(border_width),
+                center_scale_mode: SliceScaleMode::Tile { stretch_value: 1.0 },
+                sides_scale_mode: SliceScaleMode::Tile { stretch_value: 1.0 },
+                ..default()
+            }),
+        ))
+        .with_children(|b| {
+            let top = size.y/2. - border_width - inner_margin;
+            let text_style = TextStyle {
+                font: my_assets.h_font.clone(),
+                font_size: label_weight,
+                color: preview.font_color,
+            };
+            b.spawn((
+                Text2dBundle {
+                    text: Text::from_section("Next", text_style),
+                    transform: Transform::from_translation(
+                        Vec2::new(0., top - label_weight/2.).extend(0.02)),
+                    ..default()
+                },
+            ));
+
+            let slots_top = top - label_weight - inner_margin;
+            for i in 0..preview.count {
+                let pos = Vec2::new(0., slots_top - slot_size.y * (i as f32 + 0.5));
+                b.spawn((
+                    PreviewBallImage(i, None),
+                    SpatialBundle {
+                        transform: Transform::from_translation(pos.extend(0.01)),
+                        visibility: Visibility::Hidden,
+                        ..default()
+                    },
+                ));
+            }
+        });
+}
+
+pub fn update_preview_view(
+    mut commands: Commands,
+    q_player: Query<&Player>,
+    q_images: Query<(Entity, &PreviewBallImage, &Transform)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    my_assets: Res<GameAssets>,
+) {
+    let (Ok(player), Some(preview)) = (q_player.get_single(), my_assets.ui.preview_view.as_ref()) else {
+        return;
+    };
+    let slot_size = slot_size(preview);
+    let upcoming = player.upcoming_ball_levels();
+    for (entity, PreviewBallImage(idx, shown), transform) in q_images.iter() {
+        let level = upcoming.get(*idx).copied();
+        if level == *shown {
+            continue;
+        }
+        if let Some(level) = level {
+            // Large balls are shrunk to fit the slot.
+            let (w, h) = my_assets.get_ball_mesh_wh(level);
+            let scale = (slot_size.x / w).min(slot_size.y / h).min(1.);
+            let ball_view = super::create_ball_view(
+                &mut meshes, &mut materials, level,
+                Vec2::ZERO, &my_assets);
+            commands.entity(entity)
+                .insert(ball_view)
+                .insert((
+                    PreviewBallImage(*idx, Some(level)),
+                    transform.with_scale(Vec3::splat(scale)),
+                ));
+        } else {
+            commands.entity(entity)
+                .insert((
+                    PreviewBallImage(*idx, None),
+                    Visibility::Hidden,
+                ));
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use game_ron::PuzzleRon;
+
+use super::common::*;
+use super::replay::*;
+use super::FIXED_TIMESTEP_HZ;
+
+/// Time to wait for balls to settle after the last ball touched something.
+const SETTLE_SEC: f64 = 3.;
+
+/// The puzzle to play. It exists only in the puzzle mode.
+#[derive(Resource, Debug, Clone)]
+pub struct Puzzle(pub PuzzleRon);
+
+impl Puzzle {
+    pub fn next_ball_levels(&self) -> impl Iterator<Item = BallLevel> + '_ {
+        self.0.next_balls.iter().map(|l| BallLevel::new(*l))
+    }
+}
+
+/// The frame when the player ran out of balls.
+#[derive(Resource, Debug, Default)]
+pub struct OutOfBallsSince(Option<u64>);
+
+pub fn reset_out_of_balls(
+    mut since: ResMut<OutOfBallsSince>,
+) {
+    since.0 = None;
+}
+
+pub fn check_out_of_balls(
+    q_player: Query<&Player>,
+    play_frame: Res<PlayFrame>,
+    mut since: ResMut<OutOfBallsSince>,
+    mut next_state: ResMut<NextState<GameScreenState>>,
+) {
+    if let Ok(player) = q_player.get_single() {
+        // `can_drop` becomes true when the last ball touches something.
+        if !(player.is_out_of_balls && player.can_drop) {
+            return;
+        }
+        let since = *since.0.get_or_insert(play_frame.0);
+        if (play_frame.0 - since) as f64 >= SETTLE_SEC * FIXED_TIMESTEP_HZ {
+            info!("Out of balls: frame={}", play_frame.0);
+            next_state.set(GameScreenState::GameOver);
+        }
+    }
+}
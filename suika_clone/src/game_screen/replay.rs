@@ -0,0 +1,239 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use bevy_rand::prelude::*;
+use bevy_prng::ChaCha8Rng;
+use rand_core::RngCore;
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(target_arch = "wasm32"))]
+const REPLAY_FILE_PATH: &str = "last.replay.ron";
+
+/// Recorded inputs of a game.
+/// Each input is stamped with the frame number of the fixed timestep in which it was applied.
+#[derive(Debug, Clone, Default)]
+#[derive(Deserialize, Serialize)]
+pub struct Replay {
+    pub app_ver: String,
+    pub game_ron_name: String,
+    pub game_ron_asset_path: String,
+    #[serde(default)]
+    pub game_mode: GameMode,
+    /// Used only in the puzzle mode.
+    #[serde(default)]
+    pub puzzle_name: String,
+    #[serde(default)]
+    pub puzzle_asset_path: String,
+    /// Date of the daily challenge.
+    #[serde(default)]
+    pub daily: Option<String>,
+    /// Seed of the player's random generator (which decides `next_ball_level`s).
+    pub seed: u64,
+    /// (frame, input). sorted by frame.
+    pub inputs: Vec<(u64, PlayerInputEvent)>,
+}
+
+impl Replay {
+    fn new(config: &Config, seed: u64, daily: Option<String>) -> Self {
+        Self {
+            app_ver: option_env!("CARGO_PKG_VERSION")
+                .unwrap_or("0.0.0")
+                .to_string(),
+            game_ron_name: config.game_ron_name.clone(),
+            game_ron_asset_path: config.game_ron_asset_path.clone(),
+            game_mode: config.game_mode,
+            puzzle_name: config.puzzle_name.clone(),
+            puzzle_asset_path: config.puzzle_asset_path.clone(),
+            daily,
+            seed,
+            inputs: vec![],
+        }
+    }
+
+    fn inputs_at(&self, frame: u64) -> impl Iterator<Item = &PlayerInputEvent> {
+        let start = self.inputs.partition_point(|(f, _)| *f < frame);
+        self.inputs[start..].iter()
+            .take_while(move |(f, _)| *f == frame)
+            .map(|(_, ev)| ev)
+    }
+
+    pub fn read_from_file(path: &str) -> Result<Self, String> {
+        let buf = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read `{}`: {}", path, e))?;
+        ron::from_str(&buf)
+            .map_err(|e| format!("Failed to parse `{}`: {}", path, e))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn write_to_file(&self, path: &str) -> Result<(), String> {
+        let buf = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| format!("Failed to serialize a replay: {}", e))?;
+        std::fs::write(path, buf)
+            .map_err(|e| format!("Failed to write `{}`: {}", path, e))
+    }
+}
+
+/// Where `PlayerInputEvent`s come from.
+#[derive(Resource, Debug, Default)]
+pub enum ReplayMode {
+    /// From keyboard/gamepad. They are recorded.
+    #[default]
+    Record,
+    /// From a replay.
+    Play(Replay),
+}
+
+pub fn is_recording(
+    mode: Res<ReplayMode>,
+) -> bool {
+    matches!(*mode, ReplayMode::Record)
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct ReplayRecorder(Replay);
+
+/// The replay recorded at the last game over.
+#[derive(Resource, Debug, Default)]
+pub struct LastReplay(pub Option<Replay>);
+
+/// The number of fixed timestep frames since the game started.
+#[derive(Resource, Debug, Default)]
+pub struct PlayFrame(pub u64);
+
+/// Seed of the player's random generator in the current game.
+#[derive(Resource, Debug, Default)]
+pub struct PlaySeed(pub u64);
+
+/// Game mode of the current game.
+#[derive(Resource, Debug, Default)]
+pub struct PlayGameMode(pub GameMode);
+
+/// Condition of the current game. Scores are recorded with it.
+#[derive(Resource, Debug)]
+pub struct PlayCond(pub GameCond);
+
+impl Default for PlayCond {
+    fn default() -> Self {
+        Self(GameCond::new("", default()))
+    }
+}
+
+pub fn in_game_mode(
+    mode: GameMode,
+) -> impl FnMut(Res<PlayGameMode>) -> bool + Clone {
+    move |current: Res<PlayGameMode>| current.0 == mode
+}
+
+/// Inputs read from keyboard/gamepad, waiting for the next fixed timestep.
+#[derive(Resource, Debug, Default)]
+pub struct PlayerInputQueue {
+    events: Vec<PlayerInputEvent>,
+    /// Direction held in the last frame.
+    /// It's sent as `PlayerInputEvent::Move` on every fixed timestep,
+    /// so that the moving speed doesn't depend on the frame rate.
+    lr: f32,
+}
+
+impl PlayerInputQueue {
+    pub fn push(&mut self, ev: PlayerInputEvent) {
+        self.events.push(ev);
+    }
+
+    /// Directions from keyboard and gamepad are added.
+    pub fn add_move(&mut self, lr: f32) {
+        self.lr = (self.lr + lr).clamp(-1., 1.);
+    }
+}
+
+/// Before reading the held direction of the frame.
+pub fn reset_held_move(
+    mut input_queue: ResMut<PlayerInputQueue>,
+) {
+    input_queue.lr = 0.;
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn setup_replay(
+    mut play_frame: ResMut<PlayFrame>,
+    mut play_seed: ResMut<PlaySeed>,
+    mut play_game_mode: ResMut<PlayGameMode>,
+    mut play_cond: ResMut<PlayCond>,
+    mut recorder: ResMut<ReplayRecorder>,
+    mut input_queue: ResMut<PlayerInputQueue>,
+    mut global_ent: ResMut<GlobalEntropy<ChaCha8Rng>>,
+    mode: Res<ReplayMode>,
+    config: Res<Config>,
+) {
+    play_frame.0 = 0;
+    *input_queue = default();
+    let daily = match mode.as_ref() {
+        ReplayMode::Record => config.is_daily.then(daily_date_today),
+        ReplayMode::Play(replay) => replay.daily.clone(),
+    };
+    play_seed.0 = match (mode.as_ref(), daily.as_deref()) {
+        (ReplayMode::Record, Some(date)) => daily_seed(date, &config.game_ron_name),
+        (ReplayMode::Record, None) => global_ent.next_u64(),
+        (ReplayMode::Play(replay), _) => replay.seed,
+    };
+    play_game_mode.0 = match mode.as_ref() {
+        ReplayMode::Record => config.game_mode,
+        ReplayMode::Play(replay) => replay.game_mode,
+    };
+    play_cond.0 = GameCond::from_config(&config, play_game_mode.0)
+        .with_daily(daily.as_deref());
+    recorder.0 = Replay::new(&config, play_seed.0, daily);
+}
+
+/// Sends the inputs of the current frame as `PlayerInputEvent`s.
+pub fn dispatch_player_inputs(
+    mut play_frame: ResMut<PlayFrame>,
+    mut input_queue: ResMut<PlayerInputQueue>,
+    mut recorder: ResMut<ReplayRecorder>,
+    mode: Res<ReplayMode>,
+    mut ev_player_act: EventWriter<PlayerInputEvent>,
+) {
+    play_frame.0 += 1;
+    let frame = play_frame.0;
+    let inputs = std::mem::take(&mut input_queue.events);
+
+    match mode.as_ref() {
+        ReplayMode::Record => {
+            let lr = input_queue.lr;
+            let held_move = (lr != 0.).then_some(PlayerInputEvent::Move(lr));
+            for ev in held_move.into_iter().chain(inputs) {
+                recorder.0.inputs.push((frame, ev));
+                ev_player_act.send(ev);
+            }
+        },
+        ReplayMode::Play(replay) => {
+            // While replaying, a user can only pause the game.
+            // And recorded pauses are skipped because they don't affect the game.
+            let is_pause = |ev: &PlayerInputEvent| matches!(ev, PlayerInputEvent::Pause);
+            for ev in inputs.into_iter().filter(is_pause) {
+                ev_player_act.send(ev);
+            }
+            for ev in replay.inputs_at(frame).filter(|ev| !is_pause(ev)) {
+                ev_player_act.send(*ev);
+            }
+        },
+    }
+}
+
+pub fn finish_recording(
+    mut recorder: ResMut<ReplayRecorder>,
+    mut last_replay: ResMut<LastReplay>,
+) {
+    let replay = std::mem::take(&mut recorder.0);
+
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Err(e) = replay.write_to_file(REPLAY_FILE_PATH) {
+        warn!("{}", e);
+    }
+
+    last_replay.0 = Some(replay);
+}
+
+pub fn reset_replay_mode(
+    mut mode: ResMut<ReplayMode>,
+) {
+    *mode = ReplayMode::Record;
+}
@@ -0,0 +1,523 @@
+use super::*;
+use super::headless::*;
+
+fn new_idle_game() -> HeadlessGame {
+    HeadlessGame::new(&kao_game_ron(), Replay::default())
+}
+
+/// Drops 2 balls of `level` overlapping each other at the center of the bottle.
+fn drop_pair(game: &mut HeadlessGame, level: BallLevel) {
+    let world = game.world_mut();
+    let assets = world.resource::<GameAssets>();
+    let center = assets.bottle_center();
+    let r = assets.get_ball_r(level);
+    world.send_event(BallSpawnEvent::Drop(center - Vec2::X * r * 0.9, level));
+    world.send_event(BallSpawnEvent::Drop(center + Vec2::X * r * 0.9, level));
+}
+
+fn put_ball(game: &mut HeadlessGame, pos: Vec2) {
+    game.world_mut().spawn((
+        Ball::new(BallLevel(1)),
+        SpatialBundle::from_transform(Transform::from_translation(pos.extend(Z_BALL))),
+    ));
+}
+
+#[test]
+fn touches_of_a_ball_are_coalesced() {
+    let [a, b, c, d] = [1, 2, 3, 4].map(Entity::from_raw);
+
+    let touches = coalesce_touches(vec![(a, c), (a, b), (c, d)]).collect_vec();
+
+    assert_eq!(touches, vec![(a, c)]);
+    let entities = touches.iter().flat_map(|&(e1, e2)| [e1, e2]).collect_vec();
+    assert!(entities.iter().all_unique());
+}
+
+#[test]
+fn balls_of_same_level_are_combined() {
+    let mut game = new_idle_game();
+    drop_pair(&mut game, BallLevel(3));
+
+    game.step(60);
+
+    assert_eq!(game.balls().into_iter().map(|(l, _)| l).collect_vec(), vec![BallLevel(4)]);
+    assert_eq!(game.score(), 3 * 3);
+}
+
+#[test]
+fn three_balls_touching_at_once_are_combined_once() {
+    let mut game = new_idle_game();
+    let level = BallLevel(3);
+    let world = game.world_mut();
+    let assets = world.resource::<GameAssets>();
+    let center = assets.bottle_center();
+    let r = assets.get_ball_r(level);
+    for x in [-0.9, 0., 0.9] {
+        world.send_event(BallSpawnEvent::Drop(center + Vec2::X * r * x, level));
+    }
+
+    game.step(60);
+
+    assert_eq!(game.balls().into_iter().map(|(l, _)| l).sorted().collect_vec(), vec![BallLevel(3), BallLevel(4)]);
+    assert_eq!(game.score(), 3 * 3);
+}
+
+#[test]
+fn balls_of_other_shapes_are_combined() {
+    let shapes = [
+        game_ron::BallShapeRon::Ellipse { width: 2., height: 1.5, vertices: 16 },
+        game_ron::BallShapeRon::RoundedPolygon {
+            vertices: vec![Vec2::new(-0.5, -0.4), Vec2::new(0.5, -0.4), Vec2::new(0., 0.65)],
+            radius: 0.3,
+        },
+        game_ron::BallShapeRon::Capsule { length: 0.8, radius: 0.6 },
+        game_ron::BallShapeRon::Circles(vec![(Vec2::new(-0.4, 0.), 0.6), (Vec2::new(0.4, 0.), 0.6)]),
+    ];
+    for shape in shapes {
+        let mut game_ron = kao_game_ron();
+        game_ron.balls.iter_mut().for_each(|b| b.shape = shape.clone());
+        assert_eq!(game_ron.validate(), vec![]);
+        let mut game = HeadlessGame::new(&game_ron, Replay::default());
+        drop_pair(&mut game, BallLevel(3));
+
+        game.step(60);
+
+        assert_eq!(game.balls().into_iter().map(|(l, _)| l).collect_vec(), vec![BallLevel(4)], "{:?}", shape);
+    }
+}
+
+#[test]
+fn ball_physics_of_level_overrides_global_one() {
+    let mut game_ron = kao_game_ron();
+    game_ron.balls[2].physics = game_ron::BallPhysicsRon {
+        restitution: Some(game_ron::RestitutionRon { coef: 0.9 }),
+        mass: Some(100.),
+        linear_damping: Some(0.5),
+        ..default()
+    };
+    assert_eq!(game_ron.validate(), vec![]);
+    let global_restitution = game_ron.ball_physics.restitution.coef;
+    let mut game = HeadlessGame::new(&game_ron, Replay::default());
+    drop_pair(&mut game, BallLevel(2));
+    drop_pair(&mut game, BallLevel(3));
+
+    game.step(1);
+
+    let world = game.world_mut();
+    let assets = world.resource::<GameAssets>();
+    let r = assets.get_ball_r(BallLevel(3));
+    let density = assets.get_ball_density(BallLevel(3));
+    assert!((density * PI * r * r - 100.).abs() < 0.01);
+    let balls = world.query::<(&Ball, &Restitution, &ColliderDensity, &LinearDamping)>()
+        .iter(world)
+        .map(|(b, rest, d, damping)| (b.level, rest.coefficient, d.0, damping.0))
+        .collect_vec();
+    assert_eq!(balls.len(), 4);
+    for (level, restitution, d, damping) in balls {
+        if level == BallLevel(3) {
+            assert_eq!((restitution, d, damping), (0.9, density, 0.5));
+        } else {
+            assert_eq!((restitution, d, damping), (global_restitution, 1., 0.));
+        }
+    }
+}
+
+#[test]
+fn balls_of_max_level_disappear() {
+    let mut game = new_idle_game();
+    let max = game.world_mut().resource::<GameAssets>().get_ball_max_level();
+    drop_pair(&mut game, max);
+
+    game.step(60);
+
+    assert!(game.balls().is_empty());
+    assert_eq!(game.score(), (max.0 * max.0) as u32);
+}
+
+#[test]
+fn scoring_of_game_ron_is_used() {
+    let game_ron = game_ron::GameRon {
+        scoring: game_ron::ScoringRon {
+            drop_points: 5,
+            max_level_bonus: 100,
+            ..default()
+        },
+        ..kao_game_ron()
+    };
+    let mut game = HeadlessGame::new(&game_ron, Replay::default());
+    let max = game.world_mut().resource::<GameAssets>().get_ball_max_level();
+    drop_pair(&mut game, max);
+
+    game.step(60);
+
+    assert!(game.balls().is_empty());
+    assert_eq!(game.score(), 5 * 2 + (max.0 * max.0) as u32 + 100);
+}
+
+#[test]
+fn end_bonus_counts_balls_left() {
+    let scoring = ScoringDef::from_ron(&game_ron::ScoringRon {
+        end_bonus: game_ron::EndBonusRon { per_ball: 10, per_level: 1 },
+        ..default()
+    }, BallLevel(11));
+    assert_eq!(scoring.get_combine_points(BallLevel(3)), 9);
+    assert_eq!(scoring.get_end_bonus([BallLevel(1), BallLevel(4)]), 10 + 1 + 10 + 4);
+}
+
+#[test]
+fn ball_on_area_border_is_not_game_over() {
+    let mut game = new_idle_game();
+    let game_ron::Area { min_x, max_x, min_y, max_y } = game.world_mut().resource::<GameAssets>().physics.area;
+    put_ball(&mut game, Vec2::new(min_x, min_y));
+    put_ball(&mut game, Vec2::new(max_x, max_y));
+
+    game.step(2);
+
+    assert!(!game.is_game_over());
+}
+
+fn game_over_grace_frames(game: &mut HeadlessGame) -> u64 {
+    let sec = game.world_mut().resource::<GameAssets>().physics.game_over_grace_sec;
+    (sec as f64 * FIXED_TIMESTEP_HZ).round() as u64
+}
+
+#[test]
+fn ball_out_of_area_is_game_over() {
+    let mut game = new_idle_game();
+    let area = game.world_mut().resource::<GameAssets>().physics.area.clone();
+    put_ball(&mut game, Vec2::new(area.max_x + 1., (area.min_y + area.max_y) / 2.));
+    let grace_frames = game_over_grace_frames(&mut game);
+
+    game.step(grace_frames + 2); // The state changes in the next frame.
+
+    assert!(game.is_game_over());
+    let world = game.world_mut();
+    let protruded = world.query_filtered::<(), With<AreaProtruded>>()
+        .iter(world)
+        .count();
+    assert_eq!(protruded, 1);
+}
+
+#[test]
+fn ball_back_in_area_within_grace_is_not_game_over() {
+    let mut game = new_idle_game();
+    let area = game.world_mut().resource::<GameAssets>().physics.area.clone();
+    let grace_frames = game_over_grace_frames(&mut game);
+    assert!(grace_frames > 2);
+    let y = (area.min_y + area.max_y) / 2.;
+    put_ball(&mut game, Vec2::new(area.max_x + 1., y));
+
+    game.step(grace_frames / 2);
+    assert!(!game.is_game_over());
+    let world = game.world_mut();
+    let mut q_ball = world.query_filtered::<&mut Transform, With<Ball>>();
+    q_ball.single_mut(world).translation.x = area.max_x - 1.;
+    game.step(grace_frames);
+
+    assert!(!game.is_game_over());
+    let world = game.world_mut();
+    let out_of_area = world.query_filtered::<(), With<OutOfAreaSince>>()
+        .iter(world)
+        .count();
+    assert_eq!(out_of_area, 0);
+}
+
+#[test]
+fn ball_above_warning_line_warns() {
+    let mut game = new_idle_game();
+    let assets = game.world_mut().resource::<GameAssets>();
+    let bottle = &assets.bottle_settings;
+    let line_y = bottle.inner_bottom_center().y + bottle.warning_line_height.unwrap();
+    let center = assets.bottle_center();
+
+    put_ball(&mut game, Vec2::new(center.x, center.y));
+    game.step(2);
+    assert!(!game.world_mut().resource::<warning::Warning>().0);
+
+    put_ball(&mut game, Vec2::new(center.x, line_y));
+    game.step(2);
+    assert!(game.world_mut().resource::<warning::Warning>().0);
+}
+
+#[test]
+fn balls_slide_down_bottle_outline() {
+    let mut game_ron = kao_game_ron();
+    game_ron.bottle.outline = Some(vec![
+        Vec2::new(-333., 585.), Vec2::new(-100., 0.), Vec2::new(100., 0.), Vec2::new(333., 585.),
+    ]);
+    assert_eq!(game_ron.validate(), vec![]);
+    let drops = [ScriptedDrop { x: -300., wait_frames: 180 }];
+    let mut game = HeadlessGame::new(&game_ron, replay_from_drops(&game_ron, 0, &drops));
+
+    game.step(240);
+
+    assert!(!game.is_game_over());
+    let balls = game.balls();
+    assert_eq!(balls.len(), 1);
+    assert!(balls[0].1.x.abs() < 100., "{:?}", balls);
+}
+
+#[test]
+fn player_moves_within_opening_of_bottle_outline() {
+    let mut game_ron = kao_game_ron();
+    game_ron.bottle.outline = Some(vec![
+        Vec2::new(-200., 585.), Vec2::new(-333., 300.), Vec2::new(-333., 0.),
+        Vec2::new(333., 0.), Vec2::new(333., 300.), Vec2::new(200., 585.),
+    ]);
+    assert_eq!(game_ron.validate(), vec![]);
+    let drops = [ScriptedDrop { x: -333., wait_frames: 10 }];
+    let mut game = HeadlessGame::new(&game_ron, replay_from_drops(&game_ron, 0, &drops));
+
+    game.step(80);
+
+    let world = game.world_mut();
+    let x = world.query_filtered::<&Transform, With<Player>>()
+        .single(world)
+        .translation.x;
+    assert_eq!(x, -200.);
+}
+
+#[test]
+fn balls_rest_on_obstacles_in_bottle() {
+    let mut game_ron = kao_game_ron();
+    let shelf_y = 300.;
+    game_ron.obstacles = vec![game_ron::ObstacleRon {
+        shape: game_ron::ObstacleShapeRon::Rectangle { width: 400., height: 20. },
+        position: Vec2::new(0., shelf_y),
+        rotation: 0.,
+        angular_velocity: 0.,
+        image_asset_path: "images/border2.png".to_string(),
+        physics: None,
+    }];
+    assert_eq!(game_ron.validate(), vec![]);
+    let drops = [ScriptedDrop { x: 0., wait_frames: 120 }];
+    let mut game = HeadlessGame::new(&game_ron, replay_from_drops(&game_ron, 0, &drops));
+
+    game.step(150);
+
+    let world = game.world_mut();
+    let bottom_y = world.resource::<GameAssets>().bottle_settings.inner_bottom_center().y;
+    let mut q_obstacles = world.query_filtered::<&Parent, With<obstacles::Obstacle>>();
+    let parent = q_obstacles.single(world).get();
+    assert!(world.get::<Bottle>(parent).is_some());
+    let balls = game.balls();
+    assert_eq!(balls.len(), 1);
+    assert!(balls[0].1.y > bottom_y + shelf_y, "{:?}", balls);
+}
+
+#[test]
+fn obstacles_rotate_by_angular_velocity() {
+    let mut game_ron = kao_game_ron();
+    game_ron.obstacles = vec![game_ron::ObstacleRon {
+        shape: game_ron::ObstacleShapeRon::Capsule { length: 200., radius: 10. },
+        position: Vec2::new(0., 200.),
+        rotation: 0.,
+        angular_velocity: PI,
+        image_asset_path: "images/border2.png".to_string(),
+        physics: None,
+    }];
+    let mut game = HeadlessGame::new(&game_ron, Replay::default());
+
+    game.step(31);
+
+    let world = game.world_mut();
+    let (transform, angular_velocity) = world
+        .query_filtered::<(&Transform, &AngularVelocity), With<obstacles::Obstacle>>()
+        .single(world);
+    // About half a second
+    let angle = transform.rotation.to_euler(EulerRot::XYZ).2;
+    assert!((PI / 4. ..3. * PI / 4.).contains(&angle), "{:?}", transform.rotation);
+    // Rotated by its velocity so that balls are pushed, not teleported.
+    assert!((angular_velocity.0 - PI).abs() < 0.01, "{}", angular_velocity.0);
+}
+
+#[test]
+fn rotating_obstacles_are_cleaned_up_on_restart() {
+    let mut game_ron = kao_game_ron();
+    game_ron.obstacles = vec![game_ron::ObstacleRon {
+        shape: game_ron::ObstacleShapeRon::Circle { radius: 20. },
+        position: Vec2::new(0., 200.),
+        rotation: 0.,
+        angular_velocity: PI,
+        image_asset_path: "images/border2.png".to_string(),
+        physics: None,
+    }];
+    let mut game = HeadlessGame::new(&game_ron, Replay::default());
+    game.step(1);
+    let world = game.world_mut();
+    let old = world.query_filtered::<Entity, With<obstacles::RotatingObstacle>>().single(world);
+
+    world.resource_mut::<NextState<GameScreenState>>().set(GameScreenState::Restart);
+    game.step(10);
+
+    let world = game.world_mut();
+    assert!(world.get_entity(old).is_none());
+    // Only the one spawned again is left.
+    assert_eq!(world.query_filtered::<Entity, With<obstacles::RotatingObstacle>>().iter(world).count(), 1);
+}
+
+#[test]
+fn shake_moves_bottle_by_velocity_and_back() {
+    let mut game_ron = kao_game_ron();
+    game_ron.shake.tilt = 0.1;
+    let mut game = HeadlessGame::new(&game_ron, Replay::default());
+    game.step(1);
+    let (origin, _) = game.bottle_pose();
+
+    game.world_mut().send_event(PlayerInputEvent::Shake(Vec2::new(1., 0.)));
+    game.step(30); // The peak of the shake
+
+    let (pos, angle) = game.bottle_pose();
+    assert!(pos.x > origin.x + 1., "{:?} {:?}", pos, origin);
+    assert!(angle < 0., "{}", angle);
+    let world = game.world_mut();
+    let (velocity, shaking) = world.query_filtered::<(&LinearVelocity, &Shaking), With<Bottle>>()
+        .single(world);
+    assert_ne!(velocity.0, Vec2::ZERO);
+    assert_eq!(shaking.count, 1);
+
+    game.step(60);
+
+    let (pos, angle) = game.bottle_pose();
+    assert!(pos.distance(origin) < 1., "{:?} {:?}", pos, origin);
+    assert!(angle.abs() < 0.01, "{}", angle);
+}
+
+#[test]
+fn shakes_are_limited_by_rules() {
+    let mut game_ron = kao_game_ron();
+    game_ron.shake.cooldown_sec = 1.;
+    game_ron.shake.max_shakes = Some(2);
+    let mut game = HeadlessGame::new(&game_ron, Replay::default());
+    game.step(1);
+
+    game.world_mut().send_event(PlayerInputEvent::Shake(Vec2::new(0., 1.)));
+    game.step(10);
+    game.world_mut().send_event(PlayerInputEvent::Shake(Vec2::new(0., 1.)));
+    game.step(1);
+    let world = game.world_mut();
+    assert_eq!(world.query::<&Shaking>().single(world).count, 1, "in the cooldown");
+
+    game.step(60);
+    game.world_mut().send_event(PlayerInputEvent::Shake(Vec2::new(0., 1.)));
+    game.step(70);
+    game.world_mut().send_event(PlayerInputEvent::Shake(Vec2::new(0., 1.)));
+    game.step(1);
+    let world = game.world_mut();
+    assert_eq!(world.query::<&Shaking>().single(world).count, 2, "over the limit");
+}
+
+#[test]
+fn flood_mode_scores_drops() {
+    let game_ron = kao_game_ron();
+    let drops = [-150., 0., 150.].map(|x| ScriptedDrop { x, wait_frames: 60 });
+    let replay = Replay {
+        game_mode: GameMode::Flood,
+        ..replay_from_drops(&game_ron, 0, &drops)
+    };
+    let mut game = HeadlessGame::new(&game_ron, replay);
+
+    game.step(400);
+
+    assert_eq!(game.score(), 3);
+}
+
+#[test]
+fn missions_met_are_cleared() {
+    use game_ron::MissionRon::*;
+    let mut game_ron = kao_game_ron();
+    game_ron.missions = vec![MakeBall(4), Combine(1), FewerBallsThan(2)];
+    let replay = Replay {
+        game_mode: GameMode::Mission,
+        ..default()
+    };
+    let mut game = HeadlessGame::new(&game_ron, replay);
+    drop_pair(&mut game, BallLevel(3));
+
+    game.step(60);
+
+    assert!(game.is_cleared());
+}
+
+#[test]
+fn missions_not_met_are_not_cleared() {
+    use game_ron::MissionRon::*;
+    let mut game_ron = kao_game_ron();
+    game_ron.missions = vec![MakeBall(4), Score(10000)];
+    let replay = Replay {
+        game_mode: GameMode::Mission,
+        ..default()
+    };
+    let mut game = HeadlessGame::new(&game_ron, replay);
+    drop_pair(&mut game, BallLevel(3));
+
+    game.step(60);
+
+    assert!(!game.is_cleared());
+    assert!(!game.is_game_over());
+}
+
+#[test]
+fn puzzle_balls_are_placed_and_queued() {
+    let puzzle = game_ron::PuzzleRon {
+        balls: vec![
+            game_ron::PuzzleBallRon { level: 1, position: Vec2::new(-200., 60.) },
+            game_ron::PuzzleBallRon { level: 3, position: Vec2::new(200., 60.) },
+        ],
+        next_balls: vec![2, 4],
+        goal: vec![game_ron::MissionRon::MakeBall(11)],
+    };
+    let game_ron = kao_game_ron();
+    let replay = Replay {
+        game_mode: GameMode::Puzzle,
+        ..default()
+    };
+    let mut game = HeadlessGame::new_puzzle(&game_ron, puzzle, replay);
+
+    game.step(2);
+
+    let levels = game.balls().into_iter().map(|(l, _)| l).sorted().collect_vec();
+    assert_eq!(levels, vec![BallLevel(1), BallLevel(3)]);
+    assert_eq!(game.player().next_ball_level, BallLevel(2));
+}
+
+#[test]
+fn puzzle_goal_met_is_cleared() {
+    let puzzle = game_ron::PuzzleRon {
+        balls: vec![game_ron::PuzzleBallRon { level: 1, position: Vec2::new(0., 60.) }],
+        next_balls: vec![1],
+        goal: vec![game_ron::MissionRon::MakeBall(2)],
+    };
+    let game_ron = kao_game_ron();
+    let replay = Replay {
+        game_mode: GameMode::Puzzle,
+        ..replay_from_drops(&game_ron, 0, &[ScriptedDrop { x: 0., wait_frames: 0 }])
+    };
+    let mut game = HeadlessGame::new_puzzle(&game_ron, puzzle, replay);
+
+    game.step(300);
+
+    assert!(game.is_cleared());
+}
+
+#[test]
+fn puzzle_out_of_balls_is_game_over() {
+    let puzzle = game_ron::PuzzleRon {
+        balls: vec![],
+        next_balls: vec![1],
+        goal: vec![game_ron::MissionRon::MakeBall(11)],
+    };
+    let game_ron = kao_game_ron();
+    let replay = Replay {
+        game_mode: GameMode::Puzzle,
+        ..replay_from_drops(&game_ron, 0, &[ScriptedDrop { x: 0., wait_frames: 0 }])
+    };
+    let mut game = HeadlessGame::new_puzzle(&game_ron, puzzle, replay);
+
+    game.step(60);
+    assert!(game.player().is_out_of_balls);
+    assert!(!game.is_game_over());
+
+    game.step(600);
+    assert!(game.is_game_over());
+}
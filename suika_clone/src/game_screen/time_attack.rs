@@ -0,0 +1,88 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+
+use super::common::*;
+use super::replay::*;
+use super::FIXED_TIMESTEP_HZ;
+
+fn remaining_sec(my_assets: &GameAssets, frame: u64) -> f64 {
+    (my_assets.physics.time_attack_sec as f64 - frame as f64 / FIXED_TIMESTEP_HZ).max(0.)
+}
+
+pub fn check_time_over(
+    play_frame: Res<PlayFrame>,
+    my_assets: Res<GameAssets>,
+    mut next_state: ResMut<NextState<GameScreenState>>,
+) {
+    if remaining_sec(&my_assets, play_frame.0) <= 0. {
+        info!("Time over: frame={}", play_frame.0);
+        next_state.set(GameScreenState::GameOver);
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct CountdownView;
+
+#[derive(Component, Debug)]
+pub struct CountdownText;
+
+pub fn spawn_countdown_view(
+    mut commands: Commands,
+    my_assets: Res<GameAssets>,
+) {
+    let border_width = my_assets.ui.score_view.border_width;
+    let size = my_assets.countdown_view_size();
+    let center = my_assets.countdown_view_center();
+    commands
+        .spawn((
+            CountdownView,
+            SpriteBundle { // as frame
+                texture: my_assets.ui.score_view.h_bg_image.clone(),
+                sprite: Sprite {
+                    custom_size: Some(size),
+                    ..default()
+                },
+                transform: Transform::from_translation(
+                               center.extend(Z_UI)),
+                ..default()
+            },
+            ImageScaleMode::Sliced(TextureSlicer {
+                border: BorderRect::square(border_width),
+                center_scale_mode: SliceScaleMode::Tile { stretch_value: 1.0 },
+                sides_scale_mode: SliceScaleMode::Tile { stretch_value: 1.0 },
+                ..default()
+            }),
+        ))
+        .with_children(|b| {
+            let text_style = TextStyle {
+                font: my_assets.h_font.clone(),
+                font_size: super::FONT_WEIGHT_L,
+                color: my_assets.ui.score_view.font_color,
+            };
+            b.spawn((
+                CountdownText,
+                Text2dBundle {
+                    text: Text::from_section(format_remaining(remaining_sec(&my_assets, 0)), text_style),
+                    transform: Transform::from_translation(Vec2::ZERO.extend(0.01)),
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn format_remaining(sec: f64) -> String {
+    let sec = sec.ceil() as u32;
+    format!("TIME {}:{:02}", sec / 60, sec % 60)
+}
+
+pub fn update_countdown_view(
+    mut q_text: Query<&mut Text, With<CountdownText>>,
+    play_frame: Res<PlayFrame>,
+    my_assets: Res<GameAssets>,
+) {
+    if let Ok(mut text) = q_text.get_single_mut() {
+        if let Some(section) = text.sections.first_mut() {
+            section.value = format_remaining(remaining_sec(&my_assets, play_frame.0));
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+
+use super::common::*;
+use super::{Bottle, DroppingBall};
+
+pub fn has_warning_line(
+    my_assets: Res<GameAssets>,
+) -> bool {
+    my_assets.bottle_settings.warning_line_height.is_some()
+}
+
+/// Whether balls are above the warning line.
+#[derive(Resource, Debug, Default, PartialEq)]
+pub struct Warning(pub bool);
+
+pub fn reset_warning(
+    mut warning: ResMut<Warning>,
+) {
+    warning.set_if_neq(Warning(false));
+}
+
+/// Y of the warning line in the world.
+fn line_y(my_assets: &GameAssets, height: f32) -> f32 {
+    my_assets.bottle_settings.inner_bottom_center().y + height
+}
+
+pub fn check_warning(
+    q_balls: Query<(&Ball, &Transform), Without<DroppingBall>>,
+    mut warning: ResMut<Warning>,
+    my_assets: Res<GameAssets>,
+) {
+    let Some(height) = my_assets.bottle_settings.warning_line_height else {
+        return;
+    };
+    let y = line_y(&my_assets, height);
+    let is_warning = q_balls.iter()
+        .any(|(ball, t)| t.translation.y + my_assets.get_ball_r(ball.level) > y);
+    warning.set_if_neq(Warning(is_warning));
+}
+
+#[derive(Component, Debug)]
+pub struct WarningLine;
+
+const LINE_WIDTH: f32 = 4.;
+const LINE_COLOR: Color = Color::rgba(1.0, 0.2, 0.2, 0.5);
+const BLINK_HZ: f32 = 2.;
+
+pub fn spawn_warning_line(
+    mut commands: Commands,
+    q_bottle: Query<(Entity, &Bottle)>,
+    my_assets: Res<GameAssets>,
+) {
+    let (Ok((entity, bottle)), Some(height)) = (q_bottle.get_single(), my_assets.bottle_settings.warning_line_height) else {
+        return;
+    };
+    // Child of the bottle to follow shaking.
+    let pos = Vec2::new(0., line_y(&my_assets, height) - bottle.origin.y);
+    commands.entity(entity).with_children(|b| {
+        b.spawn((
+            WarningLine,
+            SpriteBundle {
+                sprite: Sprite {
+                    color: LINE_COLOR,
+                    custom_size: Some(Vec2::new(my_assets.bottle_settings.inner_width, LINE_WIDTH)),
+                    ..default()
+                },
+                transform: Transform::from_translation(pos.extend(0.03)),
+                ..default()
+            },
+        ));
+    });
+}
+
+pub fn update_warning_view(
+    mut commands: Commands,
+    mut q_line: Query<&mut Sprite, With<WarningLine>>,
+    q_bgm: Query<&AudioSink, With<Bgm>>,
+    warning: Res<Warning>,
+    time: Res<Time>,
+    my_assets: Res<GameAssets>,
+    config: Res<Config>,
+) {
+    let alpha = if warning.0 {
+        let t = time.elapsed_seconds() * BLINK_HZ * std::f32::consts::TAU;
+        0.5 + 0.5 * t.sin()
+    } else {
+        LINE_COLOR.a()
+    };
+    for mut sprite in q_line.iter_mut() {
+        sprite.color.set_a(alpha);
+    }
+
+    let sound = &my_assets.sound;
+    if let Ok(sink) = q_bgm.get_single() {
+        sink.set_speed(if warning.0 { sound.bgm_warning_speed } else { 1.0 });
+    }
+    if warning.is_changed() && warning.0 {
+        if let Some(h_se) = &sound.h_se_warning {
+            spawn_se(
+                &mut commands,
+                h_se.clone(),
+                config.get_se_volume(sound.se_warning_scale),
+            );
+        }
+    }
+}
+
+pub fn reset_bgm_speed(
+    q_bgm: Query<&AudioSink, With<Bgm>>,
+) {
+    if let Ok(sink) = q_bgm.get_single() {
+        sink.set_speed(1.0);
+    }
+}
@@ -13,17 +13,20 @@ impl Plugin for ScLoadingScreenPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins((
             RonAssetPlugin::<GameRon>::new(&["game.ron"]),
+            RonAssetPlugin::<PuzzleRon>::new(&["puzzle.ron"]),
         ));
         app.init_gizmo_group::<MyLoadingScreenGizmos>();
 
         app.init_state::<LoadingScreenState>();
+        app.init_resource::<LoadingErrors>();
 
         app.add_systems(OnEnter(GameState::Loading), (
             kick_loading,
             setup_loading_screen,
         ));
         app.add_systems(Update, (
-            update_loading_screen,
+            update_loading_screen
+                .run_if(not(in_state(LoadingScreenState::Error))),
         ).run_if(in_state(GameState::Loading)));
         app.add_systems(OnExit(GameState::Loading), (
             cleanup_loading_screen,
@@ -59,6 +62,19 @@ impl Plugin for ScLoadingScreenPlugin {
             )
         );
 
+        app.add_systems(OnEnter(LoadingScreenState::Error),
+            (
+                cleanup_loading_screen,
+                setup_loading_error_view
+                    .after(cleanup_loading_screen),
+            )
+        );
+        app.add_systems(Update,
+            (
+                read_input_on_loading_error,
+            ).run_if(in_state(LoadingScreenState::Error))
+        );
+
     }
 }
 
@@ -69,8 +85,13 @@ enum LoadingScreenState {
     LoadingGameRon,
     LoadingGameAssets,
     Completed,
+    Error,
 }
 
+/// Reasons why loading failed. They are shown in the loading screen.
+#[derive(Resource, Debug, Default)]
+struct LoadingErrors(Vec<String>);
+
 
 fn kick_loading(
     mut next_state: ResMut<NextState<LoadingScreenState>>,
@@ -90,23 +111,58 @@ fn start_loading_game_ron(
             asset_server.load(game_ron_name),
         )
     );
+
+    let h_puzzle_ron = (config.game_mode == GameMode::Puzzle)
+        .then(|| asset_server.load(&config.puzzle_asset_path));
+    commands.insert_resource(
+        CurrentPuzzleRon(h_puzzle_ron)
+    );
 }
 
+#[allow(clippy::too_many_arguments)]
 fn wait_to_complete_loading_game_ron(
-    game_ron: Res<CurrentGameRon>,
+    current_game_ron: Res<CurrentGameRon>,
+    current_puzzle_ron: Res<CurrentPuzzleRon>,
+    game_ron: Res<Assets<GameRon>>,
+    puzzle_ron: Res<Assets<PuzzleRon>>,
     asset_server: Res<AssetServer>,
+    config: Res<Config>,
+    mut errors: ResMut<LoadingErrors>,
     mut next_state: ResMut<NextState<LoadingScreenState>>,
 ) {
-    match game_ron.get_loading_state(&asset_server) {
-        LoadingState::Completed => {
-            next_state.set(LoadingScreenState::LoadingGameAssets);
-        }
-        LoadingState::Loading => {
-            // wait for next
-        }
-        LoadingState::Error => {
-            panic!("load failed!");
-        }
+    let states = [
+        (current_game_ron.get_loading_state(&asset_server), &config.game_ron_asset_path),
+        (current_puzzle_ron.get_loading_state(&asset_server), &config.puzzle_asset_path),
+    ];
+    if let Some((_, path)) = states.iter().find(|(s, _)| *s == LoadingState::Error) {
+        errors.0 = vec![format!("Failed to load `{}`.", path)];
+        next_state.set(LoadingScreenState::Error);
+        return;
+    }
+    if states.iter().any(|(s, _)| *s == LoadingState::Loading) {
+        // wait for next
+        return;
+    }
+
+    let from_ron = game_ron.get(current_game_ron.0.id())
+        .expect("game.ron is not yet loaded.");
+    let mut invalids: Vec<String> = from_ron.validate().iter()
+        .map(|e| e.to_string())
+        .collect();
+    if config.game_mode == GameMode::Mission && from_ron.missions.is_empty() {
+        invalids.push("missions: must have at least 1 element for the Mission mode.".to_string());
+    }
+    if let Some(h_puzzle_ron) = current_puzzle_ron.0.as_ref() {
+        let puzzle = puzzle_ron.get(h_puzzle_ron.id())
+            .expect("puzzle.ron is not yet loaded.");
+        invalids.extend(puzzle.validate_with(from_ron).iter()
+            .map(|e| format!("{}: {}", config.puzzle_asset_path, e)));
+    }
+    if invalids.is_empty() {
+        next_state.set(LoadingScreenState::LoadingGameAssets);
+    } else {
+        errors.0 = invalids;
+        next_state.set(LoadingScreenState::Error);
     }
 }
 
@@ -114,47 +170,35 @@ fn load_assets_game_assets(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     current_game_ron: Res<CurrentGameRon>,
+    current_puzzle_ron: Res<CurrentPuzzleRon>,
     game_ron: Res<Assets<GameRon>>,
+    puzzle_ron: Res<Assets<PuzzleRon>>,
 ) {
     let from_ron = game_ron.get(current_game_ron.0.id())
         .expect("game.ron is not yet loaded.");
-    let balls = from_ron.balls.iter()
-        .map(|n| BallLevelDef::create_with_loading(n, &asset_server))
-        .collect();
-    let effects = from_ron.effects.iter()
-        .map(|r| EffectDef::create_with_loading(r, &asset_server))
-        .collect();
-    let player = PlayerDef::create_with_loading(&from_ron.player, &asset_server);
-    let bottle = BottleDef::create_with_loading(&from_ron.bottle, &asset_server);
-    let background = BackgroundDef::create_with_loading(&from_ron.background, &asset_server);
-    let ui = UiDef::create_with_loading(&from_ron.ui, &asset_server);
-    let sound = SoundDef::create_with_loading(&from_ron.sounds, &asset_server);
-    let ball_physics = RigitBodyDef::from_ron(&from_ron.ball_physics);
-    let bottle_physics = RigitBodyDef::from_ron(&from_ron.bottle_physics);
-    let physics = OtherParamDef::from_ron(&from_ron.physics);
-
     commands.insert_resource(
-        GameAssets::new(
-            balls,
-            effects,
-            BallLevel(from_ron.drop_ball_level_max),
-            player,
-            bottle,
-            background,
-            ui,
-            asset_server.load("embedded://suika_clone/embedded_assets/fonts/x12y12pxMaruMinyaM.ttf"),
-            sound,
-            ball_physics,
-            bottle_physics,
-            physics,
-        )
+        GameAssets::create_with_loading(from_ron, asset_server.as_ref())
     );
+
+    match current_puzzle_ron.0.as_ref() {
+        Some(h_puzzle_ron) => {
+            let puzzle = puzzle_ron.get(h_puzzle_ron.id())
+                .expect("puzzle.ron is not yet loaded.");
+            commands.insert_resource(Puzzle(puzzle.clone()));
+        },
+        None => {
+            commands.remove_resource::<Puzzle>();
+        },
+    }
 }
 
 
 fn wait_to_complete_loading_game_assets(
     asset_pack: Res<GameAssets>,
     asset_server: Res<AssetServer>,
+    current_game_ron: Res<CurrentGameRon>,
+    game_ron: Res<Assets<GameRon>>,
+    mut errors: ResMut<LoadingErrors>,
     mut next_state: ResMut<NextState<LoadingScreenState>>,
 ) {
     let state = asset_pack.get_loading_state(&asset_server);
@@ -166,7 +210,19 @@ fn wait_to_complete_loading_game_assets(
             // wait for next
         }
         LoadingState::Error => {
-            panic!("load failed!");
+            let asset_paths = game_ron.get(current_game_ron.0.id())
+                .map(|r| r.asset_paths())
+                .unwrap_or_default();
+            errors.0 = asset_pack.get_failed_asset_paths(&asset_server).iter()
+                .map(|failed| {
+                    let msg = format!("Failed to load `{}`.", failed);
+                    match asset_paths.iter().find(|(_, p)| p == failed) {
+                        Some((field, _)) => format!("{}: {}", field, msg),
+                        None => msg,
+                    }
+                })
+                .collect();
+            next_state.set(LoadingScreenState::Error);
         }
     }
 }
@@ -239,3 +295,62 @@ fn cleanup_loading_screen(
             .despawn_recursive();
     }
 }
+
+const LOADING_ERROR_LINES_MAX: usize = 12;
+
+fn setup_loading_error_view(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    errors: Res<LoadingErrors>,
+) {
+    let text_style = TextStyle {
+        font: asset_server.load("embedded://suika_clone/embedded_assets/fonts/x12y12pxMaruMinyaM.ttf"),
+        font_size: 24.0,
+        color: Color::WHITE,
+    };
+
+    let mut lines = vec!["Failed to load the game.".to_string(), "".to_string()];
+    lines.extend(errors.0.iter().take(LOADING_ERROR_LINES_MAX).cloned());
+    if errors.0.len() > LOADING_ERROR_LINES_MAX {
+        lines.push(format!("... and {} more.", errors.0.len() - LOADING_ERROR_LINES_MAX));
+    }
+    lines.push("".to_string());
+    lines.push(format!("Press [{}] to back to title.", GpKbInput::Select.get_str()));
+
+    commands.spawn((
+        ForLoadingScreen,
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::BLACK,
+                custom_size: Some(Vec2::new(1200., 800.)),
+                ..default()
+            },
+            transform: Transform::from_translation(Vec2::new(0., 0.).extend(0.0)),
+            ..default()
+        },
+    )).with_children(|b| {
+        b.spawn((
+            Text2dBundle {
+                text: Text::from_section(lines.join("\n"), text_style),
+                transform: Transform::from_translation(Vec2::new(-580., 380.).extend(0.1)),
+                text_anchor: bevy::sprite::Anchor::TopLeft,
+                ..default()
+            },
+        ));
+    });
+}
+
+fn read_input_on_loading_error(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    connected_gamepad: Option<Res<ConnectedGamePad>>,
+    buttons: Res<ButtonInput<GamepadButton>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let gamepad_pressed = connected_gamepad.as_deref()
+        .is_some_and(|&ConnectedGamePad(gamepad)|
+            buttons.any_just_pressed(to_gamepad_btn(gamepad, &GAMEPAD_BTNS_SELECT)));
+
+    if keyboard.any_just_pressed(KEYBOARD_KEYS_SELECT) || gamepad_pressed {
+        next_state.set(GameState::Title);
+    }
+}
@@ -14,6 +14,8 @@ use bevy_xpbd_2d::prelude::*;
 
 use bevy_rand::prelude::*;
 use bevy_prng::ChaCha8Rng;
+#[cfg(not(target_arch = "wasm32"))]
+use clap::Parser;
 
 mod debug;
 mod common;
@@ -52,6 +54,23 @@ use crate::embedded_assets::assets::ScEmbeddedAssetsPlugin;
 // Window Settings
 const TITLE: &str = "Suikx clone";
 
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Parser)]
+#[command(version, about, long_about = None)]
+struct Cmd {
+    /// Replay file to watch
+    #[arg(long)]
+    replay: Option<String>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn get_replay_file_from_cmd() -> Option<String> {
+    Cmd::parse().replay
+}
+#[cfg(target_arch = "wasm32")]
+fn get_replay_file_from_cmd() -> Option<String> {
+    None
+}
 
 fn main() {
     // not (release & wasm32)
@@ -102,7 +121,7 @@ fn run_app(arg: Option<&str>) {
         }),
 
 
-        PhysicsPlugins::default()
+        PhysicsPlugins::new(FixedUpdate)
             .build()
             .add(LimitVelocityPlugin),
 
@@ -140,6 +159,7 @@ fn run_app(arg: Option<&str>) {
 
     app.insert_resource(AppArgs {
         force_ron_file: arg.map(|x| x.to_string()),
+        replay_file: get_replay_file_from_cmd(),
     });
 
     app.init_state::<GameState>();
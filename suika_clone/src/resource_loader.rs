@@ -20,6 +20,25 @@ pub trait Loadable : Resource{
             LoadingState::max(a, s)
         })
     }
+
+    fn get_failed_asset_paths(&self, asset_server: &AssetServer) -> Vec<String> {
+        self.get_untyped_handles()
+        .iter()
+        .filter(|h| matches!(asset_server.get_load_state(h.id()), Some(LoadState::Failed)))
+        .filter_map(|h| h.path().map(|p| p.to_string()))
+        .collect()
+    }
+}
+
+/// Gives a handle of an asset from its path.
+pub trait HandleLoader {
+    fn load<A: Asset>(&self, path: &str) -> Handle<A>;
+}
+
+impl HandleLoader for AssetServer {
+    fn load<A: Asset>(&self, path: &str) -> Handle<A> {
+        AssetServer::load(self, path.to_string())
+    }
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
@@ -6,8 +6,7 @@ use bevy_common_assets::ron::RonAssetPlugin;
 mod common;
 use self::common::*;
 mod config_popup;
-mod list_ron;
-use list_ron::*;
+use game_ron::ListRon;
 
 
 pub struct ScTitleScreenPlugin;
@@ -109,6 +108,7 @@ struct TitleAssets {
     h_bg_image: Handle<Image>,
     h_font: Handle<Font>,
     h_list_ron: Handle<ListRon>,
+    h_puzzle_list_ron: Handle<ListRon>,
 }
 
 impl Loadable for TitleAssets {
@@ -116,6 +116,7 @@ impl Loadable for TitleAssets {
         let v = vec![
             self.h_bg_image.clone().untyped(),
             self.h_list_ron.clone().untyped(),
+            self.h_puzzle_list_ron.clone().untyped(),
         ];
         v
     }
@@ -131,6 +132,7 @@ fn start_loading(
 ) {
     asset.h_bg_image = asset_server.load("embedded://suika_clone/embedded_assets/images/title_1280x840.png");
     asset.h_list_ron = asset_server.load("ron/index.list.ron"); // should read from assets/
+    asset.h_puzzle_list_ron = asset_server.load("ron/puzzles.list.ron");
     asset.h_font = asset_server.load("embedded://suika_clone/embedded_assets/fonts/x12y12pxMaruMinyaM.ttf");
 }
 
@@ -261,9 +263,10 @@ fn update_info_text(
     config: Res<Config>,
     scores: Res<Scores>,
 ) {
-    let game_cnd = GameCond::new(&config.game_ron_name);
+    let game_cnd = GameCond::from_config(&config, config.game_mode)
+        .with_daily(config.is_daily.then(daily_date_today).as_deref());
     let highscore = scores.get_highest(&game_cnd).cloned().unwrap_or(default());
-    let info = format!("v{}, mode:{}, high-score:{}", game_cnd.app_ver, game_cnd.mode, highscore.score);
+    let info = format!("v{}, {}, mode:{}, high-score:{}", game_cnd.app_ver, game_cnd.game_ron_name, game_cnd.get_mode_str(), highscore.score);
     if let Ok(mut text) = q_text.get_single_mut() {
         if let Some(section) = text.sections.first_mut(){
             section.value = info;
@@ -273,9 +276,10 @@ fn update_info_text(
 
 fn load_args(
     mut config: ResMut<Config>,
+    mut replay_mode: ResMut<ReplayMode>,
     title_asset: Res<TitleAssets>,
     list_ron: Res<Assets<ListRon>>,
-    args: Res<AppArgs>,
+    mut args: ResMut<AppArgs>,
 ) {
     if let Some(ron_name) = args.force_ron_file.as_deref() {
         let list_ron = list_ron.get(title_asset.h_list_ron.id())
@@ -286,6 +290,23 @@ fn load_args(
             config.game_ron_asset_path.clone_from(&item.path);
         }
     }
+
+    // The replay is played only at the first start.
+    if let Some(replay_file) = args.replay_file.take() {
+        match Replay::read_from_file(&replay_file) {
+            Ok(replay) => {
+                config.game_ron_name.clone_from(&replay.game_ron_name);
+                config.game_ron_asset_path.clone_from(&replay.game_ron_asset_path);
+                config.game_mode = replay.game_mode;
+                config.puzzle_name.clone_from(&replay.puzzle_name);
+                config.puzzle_asset_path.clone_from(&replay.puzzle_asset_path);
+                *replay_mode = ReplayMode::Play(replay);
+            },
+            Err(e) => {
+                warn!("{}", e);
+            },
+        }
+    }
 }
 
 #[derive(Event, Debug)]
@@ -6,7 +6,7 @@ use bevy_egui_kbgp::KbgpEguiResponseExt;
 
 use super::TitleAssets;
 use super::TitleScreenState;
-use super::list_ron::*;
+use game_ron::{ListRon, ListRonItem};
 
 use crate::game_ron_loadable::get_default_game_ron_name_and_asset_path;
 
@@ -16,6 +16,18 @@ pub struct ConfigData {
     copy: Config,
     ron_selected: usize,
     ron_options: Vec<Option<ListRonItem>>,
+    puzzle_options: Vec<ListRonItem>,
+}
+
+impl ConfigData {
+    /// Name and asset path of the selected game.ron.
+    fn selected_ron(&self) -> (&str, &str) {
+        if let Some(ListRonItem{ name, path: file }) = &self.ron_options[self.ron_selected] {
+            (name.as_str(), file.as_str())
+        } else {
+            get_default_game_ron_name_and_asset_path()
+        }
+    }
 }
 
 
@@ -36,6 +48,10 @@ pub fn prepare(
 
     config_data.ron_options = options;
 
+    config_data.puzzle_options = list_ron.get(title_asset.h_puzzle_list_ron.id())
+        .expect("puzzles.list.ron is not loaded yet.")
+        .list.clone();
+
     let (def_name, _) = get_default_game_ron_name_and_asset_path();
 
     config_data.ron_selected = if config.game_ron_name == def_name {
@@ -55,6 +71,9 @@ const VOLUME_OPTIONS: [i32; 11] = [
     60, 70, 80, 90, 100,
 ];
 
+/// Number of scores shown in the daily leaderboard.
+const DAILY_LEADERBOARD_LEN: usize = 5;
+
 const LICENSES: &str = include_str!("licenses.yml");
 
 pub fn ui_popup(
@@ -63,15 +82,17 @@ pub fn ui_popup(
     mut config_data: ResMut<ConfigData>,
     mut next_state: ResMut<NextState<TitleScreenState>>,
     mut is_open_licenses: Local<bool>,
+    scores: Res<Scores>,
 ) {
     let (def_ron_name, _) = get_default_game_ron_name_and_asset_path();
     let ctx = contexts.ctx_mut();
     let ron_options = config_data.ron_options.clone();
+    let puzzle_options = config_data.puzzle_options.clone();
     egui::Window::new("Config")
         .anchor(Align2::CENTER_CENTER, egui::Vec2::ZERO)
         .collapsible(false)
         .show(ctx, |ui| {
-            ui.heading("Game");
+            ui.heading("Skin");
             for (idx, ron) in ron_options.iter().enumerate() {
                 let name = if let Some(ListRonItem { name, ..}) = ron {
                     name
@@ -90,6 +111,81 @@ pub fn ui_popup(
                 }
             }
 
+            ui.heading("Mode");
+            ui.horizontal(|ui| {
+                for mode in GameMode::ALL {
+                    let name = if config_data.copy.game_mode == mode {
+                        format!("* {}", mode.get_str())
+                    } else {
+                        mode.get_str().to_string()
+                    };
+                    if ui.button(name)
+                        .kbgp_navigation()
+                        .clicked() {
+                        config_data.copy.game_mode = mode;
+                    }
+                }
+            });
+
+            if config_data.copy.game_mode == GameMode::Puzzle {
+                ui.heading("Puzzle");
+                // A puzzle is unlocked by clearing the previous one.
+                let mut is_unlocked = true;
+                for item in puzzle_options.iter() {
+                    let cond = GameCond::new(&config_data.copy.game_ron_name, GameMode::Puzzle)
+                        .with_puzzle(&item.name);
+                    let is_cleared = scores.get(&cond).is_some();
+                    let name = format!("{}{}{}",
+                        if config_data.copy.puzzle_asset_path == item.path { "* " } else { "" },
+                        item.name,
+                        if is_cleared { " [CLEAR]" } else { "" },
+                    );
+                    if ui.add_enabled(is_unlocked, egui::Button::new(name))
+                        .kbgp_navigation()
+                        .clicked() {
+                        config_data.copy.puzzle_name.clone_from(&item.name);
+                        config_data.copy.puzzle_asset_path.clone_from(&item.path);
+                    }
+                    is_unlocked = is_cleared;
+                }
+            }
+
+            ui.heading("Daily");
+            ui.horizontal(|ui| {
+                for (label, is_daily) in [("Off", false), ("On", true)] {
+                    let name = if config_data.copy.is_daily == is_daily {
+                        format!("* {}", label)
+                    } else {
+                        label.to_string()
+                    };
+                    if ui.button(name)
+                        .kbgp_navigation()
+                        .clicked() {
+                        config_data.copy.is_daily = is_daily;
+                    }
+                }
+            });
+            if config_data.copy.is_daily {
+                let today = daily_date_today();
+                let (name, _) = config_data.selected_ron();
+                let mut cond = GameCond::new(name, config_data.copy.game_mode);
+                if config_data.copy.game_mode == GameMode::Puzzle {
+                    cond = cond.with_puzzle(&config_data.copy.puzzle_name);
+                }
+                let cond = cond.with_daily(Some(&today));
+                ui.label(format!("Today's ranking ({}, {})", today, cond.get_mode_str()));
+                match scores.get(&cond) {
+                    Some(list) if !list.is_empty() => {
+                        for (rank, score) in list.iter().take(DAILY_LEADERBOARD_LEN).enumerate() {
+                            ui.label(format!("{}. {:>6}", rank + 1, score.score));
+                        }
+                    },
+                    _ => {
+                        ui.label("No scores yet.");
+                    },
+                }
+            }
+
             ui.heading("Sounds");
             ui.label(format!("BGM Volume: {}", config_data.copy.bgm_volume));
             ui.horizontal(|ui| {
@@ -178,14 +274,7 @@ fn apply(
     *config = config_data.copy.clone();
 
     // read ron
-    let idx = config_data.ron_selected;
-    let ron = &config_data.ron_options[idx];
-
-    let (name, file) = if let Some(ListRonItem{ name, path: file }) = ron {
-        (name.as_str(), file.as_str())
-    } else {
-        get_default_game_ron_name_and_asset_path()
-    };
+    let (name, file) = config_data.selected_ron();
 
     config.game_ron_name = name.to_string();
     config.game_ron_asset_path = file.to_string();